@@ -0,0 +1,1078 @@
+use chrono::NaiveDate;
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::lots::LOT_REPRIS;
+use crate::pharmacie::TypeMouvement;
+use crate::{lire_chaine, lire_nombre, maintenant, Application};
+
+// Commentaire des retours en pharmacie d'un transfert annulé, repris par les
+// suggestions de réapprovisionnement pour neutraliser la sortie correspondante
+pub const MOTIF_ANNULATION_TRANSFERT: &str = "Annulation du transfert";
+
+// Ligne de l'armoire à pharmacie d'un service
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StockArmoire {
+    medicament_id: u32,
+    quantite: u32,
+    seuil_alerte: u32,
+    // Lots reçus de la pharmacie centrale et quantité restante par lot
+    #[serde(default)]
+    lots: Vec<(String, u32)>,
+}
+
+impl StockArmoire {
+    fn quantite_lot(&self, numero: &str) -> u32 {
+        self.lots
+            .iter()
+            .find(|(lot, _)| lot == numero)
+            .map(|(_, quantite)| *quantite)
+            .unwrap_or(0)
+    }
+
+    fn recalculer(&mut self) {
+        self.lots.retain(|(_, quantite)| *quantite > 0);
+        self.quantite = self.lots.iter().map(|(_, quantite)| quantite).sum();
+    }
+
+    fn crediter(&mut self, lots: &[(String, u32)]) {
+        for (numero, quantite) in lots {
+            match self.lots.iter_mut().find(|(lot, _)| lot == numero) {
+                Some((_, stock)) => *stock += quantite,
+                None => self.lots.push((numero.clone(), *quantite)),
+            }
+        }
+        self.recalculer();
+    }
+
+    fn retirer(&mut self, lots: &[(String, u32)]) {
+        for (numero, quantite) in lots {
+            if let Some((_, stock)) = self.lots.iter_mut().find(|(lot, _)| lot == numero) {
+                *stock = stock.saturating_sub(*quantite);
+            }
+        }
+        self.recalculer();
+    }
+
+    // Le stock antérieur au suivi par lot des armoires est rattaché au lot de reprise
+    pub fn reprendre_lots(&mut self) {
+        let en_lots: u32 = self.lots.iter().map(|(_, quantite)| quantite).sum();
+        if self.quantite > en_lots {
+            self.lots
+                .push((LOT_REPRIS.to_string(), self.quantite - en_lots));
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StatutTransfert {
+    Demandee,
+    // Sortie de la pharmacie centrale, en cours d'acheminement
+    Preparee,
+    Livree,
+    Refusee,
+    // Préparation annulée avant livraison, les quantités sont revenues en pharmacie
+    Annulee,
+}
+
+impl StatutTransfert {
+    fn libelle(&self) -> &'static str {
+        match self {
+            StatutTransfert::Demandee => "Demandée",
+            StatutTransfert::Preparee => "Préparée",
+            StatutTransfert::Livree => "Livrée",
+            StatutTransfert::Refusee => "Refusée",
+            StatutTransfert::Annulee => "Annulée",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LigneTransfert {
+    medicament_id: u32,
+    quantite_demandee: u32,
+    quantite_preparee: u32,
+    // Lots sortis de la pharmacie centrale à la préparation
+    #[serde(default)]
+    lots: Vec<(String, u32)>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EtapeTransfert {
+    date: String,
+    utilisateur_id: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DemandeTransfert {
+    id: u32,
+    service_id: u32,
+    lignes: Vec<LigneTransfert>,
+    statut: StatutTransfert,
+    demande: EtapeTransfert,
+    preparation: Option<EtapeTransfert>,
+    livraison: Option<EtapeTransfert>,
+}
+
+impl Application {
+    // Entrée (livraison, excédent d'inventaire) dans l'armoire d'un service, la ligne est créée au besoin
+    fn crediter_armoire(
+        &mut self,
+        service_id: u32,
+        medicament_id: u32,
+        lots: Vec<(String, u32)>,
+        type_mouvement: TypeMouvement,
+        utilisateur_id: u32,
+        commentaire: String,
+    ) {
+        let Some(service) = self.services.iter_mut().find(|s| s.id == service_id) else {
+            return;
+        };
+        match service
+            .armoire
+            .iter_mut()
+            .find(|l| l.medicament_id == medicament_id)
+        {
+            Some(ligne) => ligne.crediter(&lots),
+            None => {
+                let mut ligne = StockArmoire {
+                    medicament_id,
+                    quantite: 0,
+                    seuil_alerte: 0,
+                    lots: Vec::new(),
+                };
+                ligne.crediter(&lots);
+                service.armoire.push(ligne);
+            }
+        }
+        self.journaliser_mouvement_armoire(
+            service_id,
+            medicament_id,
+            type_mouvement,
+            lots.into_iter()
+                .map(|(numero, quantite)| (numero, quantite as i64))
+                .collect(),
+            utilisateur_id,
+            commentaire,
+        );
+    }
+
+    // Un lot d'armoire suit l'état du lot de la pharmacie centrale: périmé ou en
+    // quarantaine, il ne peut plus être consommé dans le service
+    fn lot_armoire_bloque(
+        &self,
+        medicament_id: u32,
+        numero: &str,
+        date: NaiveDate,
+    ) -> Option<&'static str> {
+        let lot = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .find(|m| m.id == medicament_id)?
+            .lot(numero)?;
+        if lot.en_quarantaine() {
+            Some("EN QUARANTAINE")
+        } else if lot.perime(date) {
+            Some("PÉRIMÉ")
+        } else {
+            None
+        }
+    }
+
+    fn quantite_utilisable(&self, ligne: &StockArmoire, date: NaiveDate) -> u32 {
+        ligne
+            .lots
+            .iter()
+            .filter(|(numero, _)| {
+                self.lot_armoire_bloque(ligne.medicament_id, numero, date)
+                    .is_none()
+            })
+            .map(|(_, quantite)| quantite)
+            .sum()
+    }
+
+    // Le seuil d'alerte porte sur le stock encore utilisable
+    fn ligne_en_alerte(&self, ligne: &StockArmoire, date: NaiveDate) -> bool {
+        ligne.seuil_alerte > 0 && self.quantite_utilisable(ligne, date) <= ligne.seuil_alerte
+    }
+
+    // Lots utilisables de l'armoire qui périment en premier (FEFO)
+    fn prelever_armoire_fefo(
+        &self,
+        ligne: &StockArmoire,
+        quantite: u32,
+        date: NaiveDate,
+    ) -> Result<Vec<(String, u32)>, String> {
+        let disponible = self.quantite_utilisable(ligne, date);
+        if quantite == 0 || quantite > disponible {
+            return Err(format!(
+                "Quantité invalide: {} utilisable(s) dans l'armoire (lots périmés ou en quarantaine exclus)",
+                disponible
+            ));
+        }
+        let medicament = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .find(|m| m.id == ligne.medicament_id);
+        let mut lots: Vec<(&String, u32, Option<i64>)> = ligne
+            .lots
+            .iter()
+            .filter(|(numero, _)| {
+                self.lot_armoire_bloque(ligne.medicament_id, numero, date)
+                    .is_none()
+            })
+            .map(|(numero, stock)| {
+                let jours = medicament
+                    .and_then(|m| m.lot(numero))
+                    .and_then(|l| l.jours_avant_peremption(date));
+                (numero, *stock, jours)
+            })
+            .collect();
+        // Un lot sans date de péremption connue passe en dernier
+        lots.sort_by_key(|(_, _, jours)| (jours.is_none(), *jours));
+        let mut restant = quantite;
+        let mut preleves = Vec::new();
+        for (numero, stock, _) in lots {
+            if restant == 0 {
+                break;
+            }
+            let pris = restant.min(stock);
+            restant -= pris;
+            preleves.push((numero.clone(), pris));
+        }
+        Ok(preleves)
+    }
+
+    // Armoires contenant encore un lot donné, pour les rappels
+    pub fn lot_dans_les_armoires(&self, numero: &str) -> Vec<(String, u32, u32)> {
+        self.services
+            .iter()
+            .flat_map(|s| s.armoire.iter().map(move |l| (s, l)))
+            .filter(|(_, l)| l.quantite_lot(numero) > 0)
+            .map(|(s, l)| (s.nom.clone(), l.medicament_id, l.quantite_lot(numero)))
+            .collect()
+    }
+
+    fn choisir_service_armoire(&self) -> Option<usize> {
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        match self.service_actif(service_id) {
+            Ok(index) => Some(index),
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                None
+            }
+        }
+    }
+
+    fn afficher_armoire(&self, index: usize) {
+        let service = &self.services[index];
+        println!("\nArmoire du service {}", service.nom);
+        if service.armoire.is_empty() {
+            println!("Armoire vide.");
+        }
+        let aujourd_hui = chrono::Local::now().date_naive();
+        for ligne in &service.armoire {
+            let texte = format!(
+                "  #{} {} : {} dont {} utilisable(s) (seuil {})",
+                ligne.medicament_id,
+                self.nom_medicament(ligne.medicament_id),
+                ligne.quantite,
+                self.quantite_utilisable(ligne, aujourd_hui),
+                ligne.seuil_alerte
+            );
+            if self.ligne_en_alerte(ligne, aujourd_hui) {
+                println!("{}", format!("{} - ⚠️ Stock faible!", texte).red());
+            } else {
+                println!("{}", texte);
+            }
+            for (numero, quantite) in &ligne.lots {
+                let texte = format!("      lot {} : {}", numero, quantite);
+                match self.lot_armoire_bloque(ligne.medicament_id, numero, aujourd_hui) {
+                    Some(etat) => println!("{}", format!("{} - {}", texte, etat).red()),
+                    None => println!("{}", texte),
+                }
+            }
+        }
+    }
+
+    fn stock_service(&self) {
+        println!("{}", "\n=== STOCK D'UN SERVICE ===".green());
+        if let Some(index) = self.choisir_service_armoire() {
+            self.afficher_armoire(index);
+        }
+    }
+
+    fn afficher_transfert(&self, transfert: &DemandeTransfert) {
+        let service = self
+            .services
+            .iter()
+            .find(|s| s.id == transfert.service_id)
+            .map(|s| s.nom.as_str())
+            .unwrap_or("?");
+        println!("{}", "-".repeat(40));
+        println!(
+            "Transfert #{} vers {} ({})",
+            transfert.id,
+            service,
+            transfert.statut.libelle()
+        );
+        for (etape, suivi) in [
+            ("Demandé", Some(&transfert.demande)),
+            ("Préparé", transfert.preparation.as_ref()),
+            ("Livré", transfert.livraison.as_ref()),
+        ] {
+            if let Some(suivi) = suivi {
+                println!(
+                    "{} le {} par {}",
+                    etape,
+                    suivi.date,
+                    self.nom_utilisateur(Some(suivi.utilisateur_id))
+                );
+            }
+        }
+        for ligne in &transfert.lignes {
+            let lots: Vec<String> = ligne
+                .lots
+                .iter()
+                .map(|(numero, quantite)| format!("lot {} x{}", numero, quantite))
+                .collect();
+            println!(
+                "  {} : {} demandé(s), {} préparé(s){}",
+                self.nom_medicament(ligne.medicament_id),
+                ligne.quantite_demandee,
+                ligne.quantite_preparee,
+                if lots.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", lots.join(", "))
+                }
+            );
+        }
+    }
+
+    fn transferts_au_statut(&self, statut: StatutTransfert) -> usize {
+        let transferts: Vec<&DemandeTransfert> = self
+            .pharmacie
+            .transferts
+            .iter()
+            .filter(|t| t.statut == statut)
+            .collect();
+        for transfert in &transferts {
+            self.afficher_transfert(transfert);
+        }
+        transferts.len()
+    }
+
+    fn demander_transfert(&mut self) {
+        println!("{}", "\n=== DEMANDE DE TRANSFERT ===".green());
+        let Some(index) = self.choisir_service_armoire() else {
+            return;
+        };
+        self.afficher_armoire(index);
+        let mut lignes: Vec<LigneTransfert> = Vec::new();
+        loop {
+            let medicament_id = lire_nombre("ID du médicament (0 pour terminer): ");
+            if medicament_id == 0 {
+                break;
+            }
+            if !self
+                .pharmacie
+                .medicaments
+                .iter()
+                .any(|m| m.id == medicament_id)
+            {
+                println!("{}", "Médicament non trouvé!".red());
+                continue;
+            }
+            let quantite = lire_nombre("Quantité demandée: ");
+            if quantite == 0 {
+                continue;
+            }
+            match lignes.iter_mut().find(|l| l.medicament_id == medicament_id) {
+                Some(ligne) => ligne.quantite_demandee += quantite,
+                None => lignes.push(LigneTransfert {
+                    medicament_id,
+                    quantite_demandee: quantite,
+                    quantite_preparee: 0,
+                    lots: Vec::new(),
+                }),
+            }
+        }
+        if lignes.is_empty() {
+            println!("{}", "Demande vide, abandon.".yellow());
+            return;
+        }
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        let id = (self.pharmacie.transferts.len() + 1) as u32;
+        self.pharmacie.transferts.push(DemandeTransfert {
+            id,
+            service_id: self.services[index].id,
+            lignes,
+            statut: StatutTransfert::Demandee,
+            demande: EtapeTransfert {
+                date: maintenant(),
+                utilisateur_id,
+            },
+            preparation: None,
+            livraison: None,
+        });
+        self.save_data();
+        println!(
+            "{}",
+            format!("\nDemande de transfert #{} enregistrée!", id).green()
+        );
+    }
+
+    // La préparation sort les quantités de la pharmacie centrale en FEFO
+    fn preparer_transfert(&mut self) {
+        println!("{}", "\n=== PRÉPARATION D'UN TRANSFERT ===".green());
+        if self.transferts_au_statut(StatutTransfert::Demandee) == 0 {
+            println!("Aucune demande en attente.");
+            return;
+        }
+        let transfert_id = lire_nombre("ID du transfert: ");
+        let Some(transfert) = self
+            .pharmacie
+            .transferts
+            .iter()
+            .find(|t| t.id == transfert_id && t.statut == StatutTransfert::Demandee)
+            .cloned()
+        else {
+            println!("{}", "Demande non trouvée ou déjà traitée!".red());
+            return;
+        };
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+
+        let mut preparees = Vec::new();
+        for ligne in &transfert.lignes {
+            let disponible = self
+                .pharmacie
+                .medicaments
+                .iter()
+                .find(|m| m.id == ligne.medicament_id)
+                .map(|m| m.stock_disponible())
+                .unwrap_or(0);
+            println!(
+                "\n{} : {} demandé(s), {} disponible(s)",
+                self.nom_medicament(ligne.medicament_id),
+                ligne.quantite_demandee,
+                disponible
+            );
+            let quantite = lire_nombre("Quantité préparée: ");
+            if quantite > ligne.quantite_demandee {
+                println!(
+                    "{}",
+                    "Quantité supérieure à la demande, ligne ignorée!".red()
+                );
+                preparees.push(Vec::new());
+                continue;
+            }
+            if quantite == 0 {
+                preparees.push(Vec::new());
+                continue;
+            }
+            match self.enregistrer_mouvement_lots(
+                ligne.medicament_id,
+                TypeMouvement::SortieService(transfert.service_id),
+                quantite,
+                None,
+                Some(utilisateur_id),
+                format!("Transfert #{}", transfert.id),
+            ) {
+                Ok(lots) => preparees.push(lots),
+                Err(erreur) => {
+                    println!("{}", erreur.red());
+                    preparees.push(Vec::new());
+                }
+            }
+        }
+
+        if preparees.iter().all(|lots| lots.is_empty()) {
+            println!(
+                "{}",
+                "Aucune quantité préparée, la demande reste en attente.".yellow()
+            );
+            return;
+        }
+        if let Some(transfert) = self
+            .pharmacie
+            .transferts
+            .iter_mut()
+            .find(|t| t.id == transfert_id)
+        {
+            for (ligne, lots) in transfert.lignes.iter_mut().zip(preparees) {
+                ligne.quantite_preparee = lots.iter().map(|(_, quantite)| quantite).sum();
+                ligne.lots = lots;
+            }
+            transfert.statut = StatutTransfert::Preparee;
+            transfert.preparation = Some(EtapeTransfert {
+                date: maintenant(),
+                utilisateur_id,
+            });
+        }
+        self.save_data();
+        println!("{}", "\nTransfert préparé!".green());
+    }
+
+    // Réception dans le service: les lots préparés entrent dans l'armoire
+    fn livrer_transfert(&mut self) {
+        println!("{}", "\n=== LIVRAISON D'UN TRANSFERT ===".green());
+        if self.transferts_au_statut(StatutTransfert::Preparee) == 0 {
+            println!("Aucun transfert en cours d'acheminement.");
+            return;
+        }
+        let transfert_id = lire_nombre("ID du transfert: ");
+        let Some(transfert) = self
+            .pharmacie
+            .transferts
+            .iter()
+            .find(|t| t.id == transfert_id && t.statut == StatutTransfert::Preparee)
+            .cloned()
+        else {
+            println!("{}", "Transfert non trouvé ou non préparé!".red());
+            return;
+        };
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        for ligne in transfert.lignes.iter().filter(|l| l.quantite_preparee > 0) {
+            // Transfert préparé avant le suivi par lot des armoires
+            let lots = if ligne.lots.is_empty() {
+                vec![(LOT_REPRIS.to_string(), ligne.quantite_preparee)]
+            } else {
+                ligne.lots.clone()
+            };
+            self.crediter_armoire(
+                transfert.service_id,
+                ligne.medicament_id,
+                lots,
+                TypeMouvement::Entree,
+                utilisateur_id,
+                format!("Transfert #{}", transfert.id),
+            );
+        }
+        if let Some(transfert) = self
+            .pharmacie
+            .transferts
+            .iter_mut()
+            .find(|t| t.id == transfert_id)
+        {
+            transfert.statut = StatutTransfert::Livree;
+            transfert.livraison = Some(EtapeTransfert {
+                date: maintenant(),
+                utilisateur_id,
+            });
+        }
+        self.save_data();
+        println!("{}", "\nTransfert livré dans le service!".green());
+    }
+
+    // Un transfert préparé mais non livré rend ses lots à la pharmacie centrale
+    fn annuler_transfert(&mut self) {
+        println!("{}", "\n=== ANNULATION D'UN TRANSFERT PRÉPARÉ ===".green());
+        if self.transferts_au_statut(StatutTransfert::Preparee) == 0 {
+            println!("Aucun transfert en cours d'acheminement.");
+            return;
+        }
+        let transfert_id = lire_nombre("ID du transfert: ");
+        let Some(transfert) = self
+            .pharmacie
+            .transferts
+            .iter()
+            .find(|t| t.id == transfert_id && t.statut == StatutTransfert::Preparee)
+            .cloned()
+        else {
+            println!("{}", "Transfert non trouvé ou non préparé!".red());
+            return;
+        };
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        for ligne in transfert.lignes.iter().filter(|l| l.quantite_preparee > 0) {
+            if ligne.lots.is_empty() {
+                println!(
+                    "{}",
+                    format!(
+                        "⚠️ {} : lots non tracés, retour à saisir en pharmacie",
+                        self.nom_medicament(ligne.medicament_id)
+                    )
+                    .yellow()
+                );
+                continue;
+            }
+            for (numero, quantite) in &ligne.lots {
+                if let Err(erreur) = self.enregistrer_mouvement(
+                    ligne.medicament_id,
+                    TypeMouvement::Retour,
+                    *quantite,
+                    Some(numero),
+                    Some(utilisateur_id),
+                    format!("{} #{}", MOTIF_ANNULATION_TRANSFERT, transfert.id),
+                ) {
+                    println!("{}", erreur.red());
+                }
+            }
+        }
+        if let Some(transfert) = self
+            .pharmacie
+            .transferts
+            .iter_mut()
+            .find(|t| t.id == transfert_id)
+        {
+            transfert.statut = StatutTransfert::Annulee;
+        }
+        self.save_data();
+        println!(
+            "{}",
+            "\nTransfert annulé, stock rendu à la pharmacie!".green()
+        );
+    }
+
+    fn refuser_transfert(&mut self) {
+        println!("{}", "\n=== REFUS D'UNE DEMANDE ===".green());
+        if self.transferts_au_statut(StatutTransfert::Demandee) == 0 {
+            println!("Aucune demande en attente.");
+            return;
+        }
+        let transfert_id = lire_nombre("ID du transfert: ");
+        match self
+            .pharmacie
+            .transferts
+            .iter_mut()
+            .find(|t| t.id == transfert_id && t.statut == StatutTransfert::Demandee)
+        {
+            Some(transfert) => {
+                transfert.statut = StatutTransfert::Refusee;
+                self.save_data();
+                println!("{}", "\nDemande refusée.".green());
+            }
+            None => println!("{}", "Demande non trouvée ou déjà traitée!".red()),
+        }
+    }
+
+    // Consommation servie en FEFO parmi les lots utilisables de l'armoire
+    fn consommer_armoire(&mut self) {
+        println!("{}", "\n=== CONSOMMATION DANS UN SERVICE ===".green());
+        let Some(index) = self.choisir_service_armoire() else {
+            return;
+        };
+        self.afficher_armoire(index);
+        let medicament_id = lire_nombre("ID du médicament: ");
+        let quantite = lire_nombre("Quantité: ");
+        let patient_id = lire_nombre("ID du patient (0 si aucun): ");
+        let patient_id = Some(patient_id).filter(|id| *id != 0);
+        if patient_id.is_some_and(|id| !self.patients.iter().any(|p| p.id == id)) {
+            println!("{}", "Patient non trouvé!".red());
+            return;
+        }
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let prelevement = match self.services[index]
+            .armoire
+            .iter()
+            .find(|l| l.medicament_id == medicament_id)
+        {
+            Some(ligne) => self.prelever_armoire_fefo(ligne, quantite, aujourd_hui),
+            None => Err("Médicament absent de l'armoire!".to_string()),
+        };
+        let lots = match prelevement {
+            Ok(lots) => lots,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        if let Some(ligne) = self.services[index]
+            .armoire
+            .iter_mut()
+            .find(|l| l.medicament_id == medicament_id)
+        {
+            ligne.retirer(&lots);
+        }
+        let service_id = self.services[index].id;
+        self.journaliser_mouvement_armoire(
+            service_id,
+            medicament_id,
+            TypeMouvement::Consommation { patient_id },
+            lots.into_iter()
+                .map(|(numero, quantite)| (numero, -(quantite as i64)))
+                .collect(),
+            utilisateur_id,
+            String::new(),
+        );
+        self.save_data();
+        println!("{}", "\nConsommation enregistrée!".green());
+    }
+
+    // Retour d'un lot de l'armoire vers la pharmacie centrale (rappel, péremption...)
+    fn retourner_lot_armoire(&mut self) {
+        println!("{}", "\n=== RETOUR D'UN LOT À LA PHARMACIE ===".green());
+        let Some(index) = self.choisir_service_armoire() else {
+            return;
+        };
+        self.afficher_armoire(index);
+        let medicament_id = lire_nombre("ID du médicament: ");
+        let numero = lire_chaine("Numéro de lot: ");
+        let en_armoire = self.services[index]
+            .armoire
+            .iter()
+            .find(|l| l.medicament_id == medicament_id)
+            .map(|l| l.quantite_lot(&numero))
+            .unwrap_or(0);
+        let quantite = lire_nombre("Quantité retournée: ");
+        if quantite == 0 || quantite > en_armoire {
+            println!(
+                "{}",
+                format!(
+                    "Quantité invalide: {} unité(s) de ce lot dans l'armoire",
+                    en_armoire
+                )
+                .red()
+            );
+            return;
+        }
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        let service_id = self.services[index].id;
+        let commentaire = format!("Retour de l'armoire {}", self.services[index].nom);
+        if let Err(erreur) = self.enregistrer_mouvement(
+            medicament_id,
+            TypeMouvement::Retour,
+            quantite,
+            Some(&numero),
+            Some(utilisateur_id),
+            commentaire.clone(),
+        ) {
+            println!("{}", erreur.red());
+            return;
+        }
+        let lots = vec![(numero, quantite)];
+        if let Some(ligne) = self.services[index]
+            .armoire
+            .iter_mut()
+            .find(|l| l.medicament_id == medicament_id)
+        {
+            ligne.retirer(&lots);
+        }
+        self.journaliser_mouvement_armoire(
+            service_id,
+            medicament_id,
+            TypeMouvement::Retour,
+            lots.into_iter()
+                .map(|(numero, quantite)| (numero, -(quantite as i64)))
+                .collect(),
+            utilisateur_id,
+            commentaire,
+        );
+        self.save_data();
+        println!("{}", "\nLot retourné à la pharmacie!".green());
+    }
+
+    // Le compté remplace le théorique lot par lot, chaque écart est journalisé en ajustement
+    fn inventaire_armoire(&mut self) {
+        println!("{}", "\n=== INVENTAIRE D'UN SERVICE ===".green());
+        let Some(index) = self.choisir_service_armoire() else {
+            return;
+        };
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        let lignes = self.services[index].armoire.clone();
+        let service_id = self.services[index].id;
+        let mut ecarts = 0;
+        for (position, ligne) in lignes.iter().enumerate() {
+            let nom = self.nom_medicament(ligne.medicament_id);
+            let mut ajustements = Vec::new();
+            for (numero, quantite) in &ligne.lots {
+                let compte = lire_nombre(&format!(
+                    "{} lot {} (théorique {}), quantité comptée: ",
+                    nom, numero, quantite
+                ));
+                if compte == *quantite {
+                    continue;
+                }
+                let ecart = compte as i64 - *quantite as i64;
+                println!(
+                    "{}",
+                    format!("Écart de {:+} sur {} lot {}", ecart, nom, numero).yellow()
+                );
+                ajustements.push((numero.clone(), ecart));
+            }
+            if ajustements.is_empty() {
+                continue;
+            }
+            ecarts += ajustements.len();
+            let armoire = &mut self.services[index].armoire[position];
+            for (numero, ecart) in &ajustements {
+                if let Some((_, stock)) = armoire.lots.iter_mut().find(|(lot, _)| lot == numero) {
+                    *stock = (*stock as i64 + ecart) as u32;
+                }
+            }
+            armoire.recalculer();
+            self.journaliser_mouvement_armoire(
+                service_id,
+                ligne.medicament_id,
+                TypeMouvement::Ajustement,
+                ajustements,
+                utilisateur_id,
+                "Inventaire".to_string(),
+            );
+        }
+
+        // Unités trouvées sur un lot ou un médicament absent de l'armoire
+        loop {
+            let medicament_id =
+                lire_nombre("ID d'un médicament trouvé hors inventaire (0 pour terminer): ");
+            if medicament_id == 0 {
+                break;
+            }
+            let Some(medicament) = self
+                .pharmacie
+                .medicaments
+                .iter()
+                .find(|m| m.id == medicament_id)
+            else {
+                println!("{}", "Médicament non trouvé!".red());
+                continue;
+            };
+            let numero = lire_chaine("Numéro de lot: ");
+            if numero.is_empty() {
+                println!("{}", "Numéro de lot obligatoire!".red());
+                continue;
+            }
+            if medicament.lot(&numero).is_none() {
+                println!(
+                    "{}",
+                    format!("⚠️ Lot {} inconnu de la pharmacie centrale", numero).yellow()
+                );
+            }
+            let compte = lire_nombre("Quantité comptée: ");
+            if compte == 0 {
+                continue;
+            }
+            println!(
+                "{}",
+                format!(
+                    "Écart de {:+} sur {} lot {}",
+                    compte,
+                    self.nom_medicament(medicament_id),
+                    numero
+                )
+                .yellow()
+            );
+            ecarts += 1;
+            self.crediter_armoire(
+                service_id,
+                medicament_id,
+                vec![(numero, compte)],
+                TypeMouvement::Ajustement,
+                utilisateur_id,
+                "Inventaire".to_string(),
+            );
+        }
+        self.save_data();
+        if ecarts == 0 {
+            println!("{}", "\nInventaire conforme.".green());
+        } else {
+            println!(
+                "{}",
+                format!(
+                    "\nInventaire enregistré, {} écart(s) régularisé(s).",
+                    ecarts
+                )
+                .green()
+            );
+        }
+    }
+
+    fn definir_seuil_armoire(&mut self) {
+        println!("{}", "\n=== SEUIL D'ALERTE D'UNE ARMOIRE ===".green());
+        let Some(index) = self.choisir_service_armoire() else {
+            return;
+        };
+        self.afficher_armoire(index);
+        let medicament_id = lire_nombre("ID du médicament: ");
+        if !self
+            .pharmacie
+            .medicaments
+            .iter()
+            .any(|m| m.id == medicament_id)
+        {
+            println!("{}", "Médicament non trouvé!".red());
+            return;
+        }
+        let seuil_alerte = lire_nombre("Seuil d'alerte (0 pour aucun): ");
+        let armoire = &mut self.services[index].armoire;
+        match armoire
+            .iter_mut()
+            .find(|l| l.medicament_id == medicament_id)
+        {
+            Some(ligne) => ligne.seuil_alerte = seuil_alerte,
+            None => armoire.push(StockArmoire {
+                medicament_id,
+                quantite: 0,
+                seuil_alerte,
+                lots: Vec::new(),
+            }),
+        }
+        self.save_data();
+        println!("{}", "\nSeuil d'alerte mis à jour!".green());
+    }
+
+    // Lignes d'armoire sous leur seuil et lots bloqués, service par service
+    fn afficher_alertes_armoires(&self) -> usize {
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let mut nombre = 0;
+        for service in self.services.iter().filter(|s| s.actif()) {
+            for ligne in &service.armoire {
+                if self.ligne_en_alerte(ligne, aujourd_hui) {
+                    nombre += 1;
+                    println!(
+                        "{}",
+                        format!(
+                            "{} - {} : {} utilisable(s) (seuil {})",
+                            service.nom,
+                            self.nom_medicament(ligne.medicament_id),
+                            self.quantite_utilisable(ligne, aujourd_hui),
+                            ligne.seuil_alerte
+                        )
+                        .yellow()
+                    );
+                }
+                for (numero, quantite) in &ligne.lots {
+                    if let Some(etat) =
+                        self.lot_armoire_bloque(ligne.medicament_id, numero, aujourd_hui)
+                    {
+                        nombre += 1;
+                        println!(
+                            "{}",
+                            format!(
+                                "{} - {} lot {} : {} unité(s) {}, à retourner à la pharmacie",
+                                service.nom,
+                                self.nom_medicament(ligne.medicament_id),
+                                numero,
+                                quantite,
+                                etat
+                            )
+                            .red()
+                        );
+                    }
+                }
+            }
+        }
+        nombre
+    }
+
+    pub fn alertes_armoires(&self) {
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let lignes: Vec<&StockArmoire> = self
+            .services
+            .iter()
+            .filter(|s| s.actif())
+            .flat_map(|s| &s.armoire)
+            .collect();
+        let en_alerte = lignes
+            .iter()
+            .filter(|l| self.ligne_en_alerte(l, aujourd_hui))
+            .count();
+        let bloques = lignes
+            .iter()
+            .flat_map(|l| {
+                l.lots
+                    .iter()
+                    .map(move |(numero, _)| (l.medicament_id, numero))
+            })
+            .filter(|(id, numero)| self.lot_armoire_bloque(*id, numero, aujourd_hui).is_some())
+            .count();
+        if en_alerte > 0 || bloques > 0 {
+            println!(
+                "{}",
+                format!(
+                    "⚠️ {} stock(s) de service sous le seuil d'alerte, {} lot(s) périmé(s) ou en quarantaine en armoire:",
+                    en_alerte, bloques
+                )
+                .yellow()
+            );
+            self.afficher_alertes_armoires();
+        }
+    }
+
+    fn liste_alertes_armoires(&self) {
+        println!("{}", "\n=== ALERTES DE STOCK PAR SERVICE ===".green());
+        if self.afficher_alertes_armoires() == 0 {
+            println!("Aucun stock de service sous son seuil d'alerte ni lot bloqué.");
+        }
+    }
+
+    pub fn menu_armoires(&mut self) {
+        loop {
+            println!("\n{}", "=== STOCKS DES SERVICES ===".blue().bold());
+            println!("1. Stock d'un service");
+            println!("2. Demander un transfert");
+            println!("3. Préparer un transfert");
+            println!("4. Livrer un transfert");
+            println!("5. Refuser une demande de transfert");
+            println!("6. Annuler un transfert préparé");
+            println!("7. Consommation dans un service");
+            println!("8. Retourner un lot à la pharmacie");
+            println!("9. Inventaire d'un service");
+            println!("10. Seuil d'alerte d'une armoire");
+            println!("11. Alertes de stock par service");
+            println!("12. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.stock_service(),
+                2 => self.demander_transfert(),
+                3 => self.preparer_transfert(),
+                4 => self.livrer_transfert(),
+                5 => self.refuser_transfert(),
+                6 => self.annuler_transfert(),
+                7 => self.consommer_armoire(),
+                8 => self.retourner_lot_armoire(),
+                9 => self.inventaire_armoire(),
+                10 => self.definir_seuil_armoire(),
+                11 => self.liste_alertes_armoires(),
+                12 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
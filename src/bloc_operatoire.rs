@@ -0,0 +1,655 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::gardes::est_jour_special;
+use crate::planning::parse_heure;
+use crate::reservations::ObjetReservation;
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date, parse_date_heure, Application,
+    FORMAT_DATE_HEURE,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SalleOperation {
+    id: u32,
+    nom: String,
+    ouverture: String,
+    fermeture: String,
+}
+
+impl SalleOperation {
+    // Plage d'ouverture de la salle pour un jour donné
+    fn plage(&self, jour: NaiveDate) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        Some((
+            jour.and_time(parse_heure(&self.ouverture)?),
+            jour.and_time(parse_heure(&self.fermeture)?),
+        ))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StatutIntervention {
+    Programmee,
+    EnSalle,
+    Terminee,
+    Annulee,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InterventionChirurgicale {
+    id: u32,
+    salle_id: u32,
+    patient_id: u32,
+    chirurgien_id: u32,
+    anesthesiste_id: u32,
+    acte: String,
+    debut: String,
+    duree_estimee: u32,
+    equipements: Vec<(u32, u32)>,
+    statut: StatutIntervention,
+    entree_salle: Option<String>,
+    sortie_salle: Option<String>,
+}
+
+impl InterventionChirurgicale {
+    pub fn patient_id(&self) -> u32 {
+        self.patient_id
+    }
+
+    pub fn mobilise(&self, personnel_id: u32) -> bool {
+        self.chirurgien_id == personnel_id || self.anesthesiste_id == personnel_id
+    }
+
+    fn debut_prevu(&self) -> Option<NaiveDateTime> {
+        parse_date_heure(&self.debut)
+    }
+
+    // Occupation réelle si la salle a été pointée, sinon occupation prévue.
+    // Une intervention en cours occupe au moins jusqu'à sa fin prévue.
+    pub fn creneau(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let debut = match &self.entree_salle {
+            Some(entree) => parse_date_heure(entree)?,
+            None => self.debut_prevu()?,
+        };
+        let fin = match (&self.sortie_salle, self.statut) {
+            (Some(sortie), _) => parse_date_heure(sortie)?,
+            (None, StatutIntervention::EnSalle) => {
+                let prevue = debut + Duration::minutes(self.duree_estimee as i64);
+                prevue.max(chrono::Local::now().naive_local())
+            }
+            (None, _) => debut + Duration::minutes(self.duree_estimee as i64),
+        };
+        Some((debut, fin))
+    }
+
+    fn duree_reelle(&self) -> Option<Duration> {
+        let entree = parse_date_heure(self.entree_salle.as_deref()?)?;
+        let sortie = parse_date_heure(self.sortie_salle.as_deref()?)?;
+        Some(sortie - entree)
+    }
+
+    fn active(&self) -> bool {
+        self.statut != StatutIntervention::Annulee
+    }
+
+    fn chevauche(&self, debut: NaiveDateTime, fin: NaiveDateTime) -> bool {
+        self.active() && self.creneau().is_some_and(|(d, f)| d < fin && debut < f)
+    }
+}
+
+fn lire_horodatage(message: &str) -> Option<String> {
+    let saisie = lire_chaine(message);
+    if saisie.is_empty() {
+        return Some(maintenant());
+    }
+    parse_date_heure(&saisie).map(|d| d.format(FORMAT_DATE_HEURE).to_string())
+}
+
+impl Application {
+    // Interventions actives occupant une partie du créneau
+    pub fn interventions_chevauchant(
+        &self,
+        debut: NaiveDateTime,
+        fin: NaiveDateTime,
+    ) -> impl Iterator<Item = &InterventionChirurgicale> {
+        self.interventions_chirurgicales
+            .iter()
+            .filter(move |i| i.chevauche(debut, fin))
+    }
+
+    // Conflits de salle, de personnel et de patient pour une intervention envisagée.
+    // Les équipements sont contrôlés à la réservation.
+    fn conflits_intervention(&self, cas: &InterventionChirurgicale) -> Vec<String> {
+        let mut conflits = Vec::new();
+        let (debut, fin) = match cas.creneau() {
+            Some(creneau) => creneau,
+            None => return vec!["Date ou heure invalide".to_string()],
+        };
+
+        if let Some(salle) = self.salles_operation.iter().find(|s| s.id == cas.salle_id) {
+            match salle.plage(debut.date()) {
+                Some((ouverture, fermeture)) if ouverture <= debut && fin <= fermeture => {}
+                _ => conflits.push(format!(
+                    "Hors des heures d'ouverture de {} ({} - {})",
+                    salle.nom, salle.ouverture, salle.fermeture
+                )),
+            }
+        }
+
+        for (role, personnel_id) in [
+            ("Le chirurgien", cas.chirurgien_id),
+            ("L'anesthésiste", cas.anesthesiste_id),
+        ] {
+            let membre = match self.personnel.iter().find(|p| p.id == personnel_id) {
+                Some(membre) if membre.actif() => membre,
+                _ => {
+                    conflits.push(format!("{} est inconnu ou a quitté l'établissement", role));
+                    continue;
+                }
+            };
+            if membre.planning.en_conge(debut.date()) {
+                conflits.push(format!("{} est en congé ce jour-là", role));
+            }
+            if let Some(rdv) = self
+                .rendez_vous
+                .iter()
+                .find(|r| r.personnel_id == personnel_id && r.chevauche(debut, fin))
+            {
+                conflits.push(format!(
+                    "{} a le rendez-vous N°{} à {}",
+                    role, rdv.id, rdv.heure
+                ));
+            }
+            if self
+                .indisponibilites
+                .iter()
+                .any(|i| i.chevauche(personnel_id, debut, fin))
+            {
+                conflits.push(format!("{} est indisponible", role));
+            }
+        }
+        if cas.chirurgien_id == cas.anesthesiste_id {
+            conflits.push("Le chirurgien et l'anesthésiste doivent être distincts".to_string());
+        }
+
+        for autre in self
+            .interventions_chevauchant(debut, fin)
+            .filter(|i| i.id != cas.id)
+        {
+            if autre.salle_id == cas.salle_id {
+                conflits.push(format!("Salle occupée par l'intervention N°{}", autre.id));
+            }
+            if autre.mobilise(cas.chirurgien_id) || autre.mobilise(cas.anesthesiste_id) {
+                conflits.push(format!(
+                    "Personnel déjà mobilisé sur l'intervention N°{}",
+                    autre.id
+                ));
+            }
+            if autre.patient_id == cas.patient_id {
+                conflits.push(format!(
+                    "Patient déjà programmé (intervention N°{})",
+                    autre.id
+                ));
+            }
+        }
+        if let Some(rdv) = self
+            .rendez_vous
+            .iter()
+            .find(|r| r.patient_id == cas.patient_id && r.chevauche(debut, fin))
+        {
+            conflits.push(format!("Le patient a le rendez-vous N°{}", rdv.id));
+        }
+        conflits
+    }
+
+    fn liste_salles(&self) {
+        for salle in &self.salles_operation {
+            println!(
+                "{}. {} ({} - {})",
+                salle.id, salle.nom, salle.ouverture, salle.fermeture
+            );
+        }
+    }
+
+    fn ajouter_salle(&mut self) {
+        println!("{}", "\n=== AJOUT D'UNE SALLE D'OPÉRATION ===".green());
+        let id = (self.salles_operation.len() + 1) as u32;
+        let nom = lire_chaine("Nom de la salle: ");
+        let ouverture = lire_chaine("Ouverture (HH:MM): ");
+        let fermeture = lire_chaine("Fermeture (HH:MM): ");
+        match (parse_heure(&ouverture), parse_heure(&fermeture)) {
+            (Some(o), Some(f)) if o < f => {}
+            _ => {
+                println!("{}", "Horaires invalides!".red());
+                return;
+            }
+        }
+        self.salles_operation.push(SalleOperation {
+            id,
+            nom,
+            ouverture,
+            fermeture,
+        });
+        self.save_data();
+        println!("{}", "\nSalle ajoutée avec succès!".green());
+    }
+
+    fn programmer_intervention(&mut self) {
+        println!("{}", "\n=== PROGRAMMATION D'UNE INTERVENTION ===".green());
+        self.liste_salles();
+        let salle_id = lire_nombre("ID de la salle: ");
+        if !self.salles_operation.iter().any(|s| s.id == salle_id) {
+            println!("{}", "Salle non trouvée!".red());
+            return;
+        }
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+        if !self.patients.iter().any(|p| p.id == patient_id) {
+            println!("{}", "Patient non trouvé!".red());
+            return;
+        }
+        self.liste_personnel();
+        let chirurgien_id = lire_nombre("ID du chirurgien: ");
+        let anesthesiste_id = lire_nombre("ID de l'anesthésiste: ");
+        let acte = lire_chaine("Acte chirurgical: ");
+        let debut = lire_chaine("Début (JJ/MM/AAAA HH:MM): ");
+        let duree_estimee = lire_nombre("Durée estimée (minutes): ");
+        if duree_estimee == 0 {
+            println!("{}", "La durée doit être positive!".red());
+            return;
+        }
+
+        let mut equipements = Vec::new();
+        while lire_chaine("Ajouter un équipement requis (o/n): ").eq_ignore_ascii_case("o") {
+            if let Some(choix) = self.choisir_equipement() {
+                if !equipements.contains(&choix) {
+                    equipements.push(choix);
+                }
+            }
+        }
+
+        let cas = InterventionChirurgicale {
+            id: (self.interventions_chirurgicales.len() + 1) as u32,
+            salle_id,
+            patient_id,
+            chirurgien_id,
+            anesthesiste_id,
+            acte,
+            debut,
+            duree_estimee,
+            equipements,
+            statut: StatutIntervention::Programmee,
+            entree_salle: None,
+            sortie_salle: None,
+        };
+        let conflits = self.conflits_intervention(&cas);
+        if !conflits.is_empty() {
+            println!("{}", "Intervention impossible:".red());
+            for conflit in conflits {
+                println!("{}", format!("  {}", conflit).red());
+            }
+            return;
+        }
+
+        // Toutes les réservations d'équipement réussissent, ou aucune n'est conservée
+        let debut = match cas.debut_prevu() {
+            Some(debut) => debut,
+            None => return,
+        };
+        let objet = ObjetReservation::Intervention(cas.id);
+        for (service_id, equipement_id) in &cas.equipements {
+            if let Err(erreur) = self.reserver_equipement(
+                *service_id,
+                *equipement_id,
+                debut,
+                cas.duree_estimee,
+                objet.clone(),
+                // Le chirurgien opère l'appareil: son habilitation est contrôlée
+                Some(cas.chirurgien_id),
+            ) {
+                self.liberer_reservations(&objet);
+                println!("{}", format!("Équipement indisponible: {}", erreur).red());
+                return;
+            }
+        }
+
+        let id = cas.id;
+        self.interventions_chirurgicales.push(cas);
+        self.save_data();
+        println!("{}", format!("\nIntervention N°{} programmée!", id).green());
+    }
+
+    fn pointer_intervention(&mut self, entree: bool) {
+        let id = lire_nombre("ID de l'intervention: ");
+        let attendu = if entree {
+            StatutIntervention::Programmee
+        } else {
+            StatutIntervention::EnSalle
+        };
+        if !self
+            .interventions_chirurgicales
+            .iter()
+            .any(|i| i.id == id && i.statut == attendu)
+        {
+            println!(
+                "{}",
+                format!("Aucune intervention {:?} N°{}!", attendu, id).red()
+            );
+            return;
+        }
+        let horodatage = match lire_horodatage("Heure (JJ/MM/AAAA HH:MM, Entrée pour maintenant): ")
+        {
+            Some(horodatage) => horodatage,
+            None => {
+                println!("{}", "Date ou heure invalide!".red());
+                return;
+            }
+        };
+
+        let cas = match self
+            .interventions_chirurgicales
+            .iter_mut()
+            .find(|i| i.id == id)
+        {
+            Some(cas) => cas,
+            None => return,
+        };
+        if entree {
+            cas.entree_salle = Some(horodatage);
+            cas.statut = StatutIntervention::EnSalle;
+            println!("{}", "\nEntrée en salle enregistrée!".green());
+            // Les équipements sont réservés à partir de l'entrée réelle
+            if let Some(debut) = cas.entree_salle.as_deref().and_then(parse_date_heure) {
+                let duree = cas.duree_estimee;
+                self.ajuster_reservations_intervention(id, debut, duree);
+            }
+        } else {
+            if cas
+                .entree_salle
+                .as_deref()
+                .and_then(parse_date_heure)
+                .zip(parse_date_heure(&horodatage))
+                .is_some_and(|(e, s)| s < e)
+            {
+                println!("{}", "La sortie précède l'entrée en salle!".red());
+                return;
+            }
+            cas.sortie_salle = Some(horodatage);
+            cas.statut = StatutIntervention::Terminee;
+            println!("{}", "\nSortie de salle enregistrée!".green());
+            if let Some(duree) = cas.duree_reelle() {
+                let depassement = duree.num_minutes() - cas.duree_estimee as i64;
+                if depassement > 0 {
+                    println!(
+                        "{}",
+                        format!("⚠️ Dépassement de {} minute(s)", depassement).yellow()
+                    );
+                }
+                // Les réservations couvrent l'occupation réelle, dépassement compris
+                if let Some(debut) = cas.entree_salle.as_deref().and_then(parse_date_heure) {
+                    let minutes = duree.num_minutes().max(1) as u32;
+                    self.ajuster_reservations_intervention(id, debut, minutes);
+                }
+            }
+        }
+        self.save_data();
+    }
+
+    fn ajuster_reservations_intervention(&mut self, id: u32, debut: NaiveDateTime, duree: u32) {
+        if let Err(erreur) =
+            self.deplacer_reservations(&ObjetReservation::Intervention(id), debut, Some(duree))
+        {
+            println!(
+                "{}",
+                format!("⚠️ Équipements non ajustés à l'horaire réel: {}", erreur).yellow()
+            );
+        }
+    }
+
+    fn annuler_intervention(&mut self) {
+        let id = lire_nombre("ID de l'intervention: ");
+        match self
+            .interventions_chirurgicales
+            .iter_mut()
+            .find(|i| i.id == id && i.statut == StatutIntervention::Programmee)
+        {
+            Some(cas) => cas.statut = StatutIntervention::Annulee,
+            None => {
+                println!("{}", "Intervention programmée non trouvée!".red());
+                return;
+            }
+        }
+        let liberees = self.liberer_reservations(&ObjetReservation::Intervention(id));
+        self.save_data();
+        println!(
+            "{}",
+            format!(
+                "\nIntervention annulée, {} équipement(s) libéré(s).",
+                liberees
+            )
+            .green()
+        );
+    }
+
+    fn programme_salle(&self) {
+        self.liste_salles();
+        let salle_id = lire_nombre("ID de la salle: ");
+        let salle = match self.salles_operation.iter().find(|s| s.id == salle_id) {
+            Some(salle) => salle,
+            None => {
+                println!("{}", "Salle non trouvée!".red());
+                return;
+            }
+        };
+        let jour = match parse_date(&lire_chaine("Date (JJ/MM/AAAA): ")) {
+            Some(jour) => jour,
+            None => {
+                println!("{}", "Date invalide!".red());
+                return;
+            }
+        };
+        let (ouverture, fermeture) = match salle.plage(jour) {
+            Some(plage) => plage,
+            None => return,
+        };
+
+        println!(
+            "{}",
+            format!("\n=== {} - {} ===", salle.nom, jour.format("%d/%m/%Y")).green()
+        );
+        let mut cas: Vec<&InterventionChirurgicale> = self
+            .interventions_chirurgicales
+            .iter()
+            .filter(|i| i.active() && i.salle_id == salle_id)
+            .filter(|i| i.creneau().is_some_and(|(d, _)| d.date() == jour))
+            .collect();
+        cas.sort_by_key(|i| i.creneau().map(|(d, _)| d));
+
+        let mut curseur = ouverture;
+        for intervention in cas {
+            let (debut, fin) = match intervention.creneau() {
+                Some(creneau) => creneau,
+                None => continue,
+            };
+            if debut > curseur {
+                println!(
+                    "{}",
+                    format!(
+                        "{} - {}  libre",
+                        curseur.format("%H:%M"),
+                        debut.format("%H:%M")
+                    )
+                    .dimmed()
+                );
+            }
+            let patient = self
+                .patients
+                .iter()
+                .find(|p| p.id == intervention.patient_id)
+                .map(|p| format!("{} {}", p.nom, p.prenom))
+                .unwrap_or_default();
+            println!(
+                "{} - {}  N°{} {} - {} ({:?})",
+                debut.format("%H:%M"),
+                fin.format("%H:%M"),
+                intervention.id,
+                intervention.acte,
+                patient,
+                intervention.statut
+            );
+            println!(
+                "               {} / {}",
+                self.nom_personnel(intervention.chirurgien_id),
+                self.nom_personnel(intervention.anesthesiste_id)
+            );
+            if intervention.entree_salle.is_some() {
+                println!(
+                    "               prévu {} ({} min)",
+                    intervention.debut, intervention.duree_estimee
+                );
+            }
+            if fin > fermeture {
+                println!("{}", "               ⚠️ Déborde après la fermeture".red());
+            }
+            curseur = curseur.max(fin);
+        }
+        if curseur < fermeture {
+            println!(
+                "{}",
+                format!(
+                    "{} - {}  libre",
+                    curseur.format("%H:%M"),
+                    fermeture.format("%H:%M")
+                )
+                .dimmed()
+            );
+        }
+    }
+
+    // Occupation des salles et dépassements sur une période
+    fn rapport_bloc(&self) {
+        println!("{}", "\n=== RAPPORT DU BLOC OPÉRATOIRE ===".green());
+        let (du, au) = match (
+            parse_date(&lire_chaine("Du (JJ/MM/AAAA): ")),
+            parse_date(&lire_chaine("Au (JJ/MM/AAAA): ")),
+        ) {
+            (Some(du), Some(au)) if du <= au => (du, au),
+            _ => {
+                println!("{}", "Période invalide!".red());
+                return;
+            }
+        };
+
+        // Le bloc programmé ne travaille que les jours ouvrés; une intervention
+        // le week-end ou un jour férié peut donc porter le taux au-delà de 100%
+        let jours_ouvres: Vec<NaiveDate> = du
+            .iter_days()
+            .take_while(|d| *d <= au)
+            .filter(|d| !est_jour_special(*d))
+            .collect();
+        println!(
+            "Base de calcul: {} jour(s) ouvré(s), week-ends et jours fériés exclus",
+            jours_ouvres.len()
+        );
+
+        for salle in &self.salles_operation {
+            let disponible: i64 = jours_ouvres
+                .iter()
+                .filter_map(|d| salle.plage(*d))
+                .map(|(o, f)| (f - o).num_minutes())
+                .sum();
+            let cas: Vec<&InterventionChirurgicale> = self
+                .interventions_chirurgicales
+                .iter()
+                .filter(|i| i.active() && i.salle_id == salle.id)
+                .filter(|i| {
+                    i.creneau()
+                        .is_some_and(|(d, _)| du <= d.date() && d.date() <= au)
+                })
+                .collect();
+            let prevu: i64 = cas.iter().map(|i| i.duree_estimee as i64).sum();
+            let terminees: Vec<&&InterventionChirurgicale> = cas
+                .iter()
+                .filter(|i| i.statut == StatutIntervention::Terminee)
+                .collect();
+            let reel: i64 = terminees
+                .iter()
+                .filter_map(|i| i.duree_reelle())
+                .map(|d| d.num_minutes())
+                .sum();
+            let depassements: Vec<(u32, i64)> = terminees
+                .iter()
+                .filter_map(|i| {
+                    let ecart = i.duree_reelle()?.num_minutes() - i.duree_estimee as i64;
+                    (ecart > 0).then_some((i.id, ecart))
+                })
+                .collect();
+            let debordements = cas
+                .iter()
+                .filter(|i| {
+                    i.creneau()
+                        .zip(i.creneau().and_then(|(d, _)| salle.plage(d.date())))
+                        .is_some_and(|((_, fin), (_, fermeture))| fin > fermeture)
+                })
+                .count();
+            let taux = |minutes: i64| {
+                if disponible > 0 {
+                    minutes as f64 * 100.0 / disponible as f64
+                } else {
+                    0.0
+                }
+            };
+
+            println!("{}", "-".repeat(40));
+            println!("{}", salle.nom.bold());
+            println!(
+                "{} intervention(s), {} terminée(s)",
+                cas.len(),
+                terminees.len()
+            );
+            println!("Occupation programmée: {:.1}%", taux(prevu));
+            println!("Occupation réelle: {:.1}%", taux(reel));
+            println!(
+                "Dépassements de durée: {} ({} min au total)",
+                depassements.len(),
+                depassements.iter().map(|(_, m)| m).sum::<i64>()
+            );
+            for (id, minutes) in &depassements {
+                println!("  Intervention N°{}: +{} min", id, minutes);
+            }
+            if debordements > 0 {
+                println!(
+                    "{}",
+                    format!("{} intervention(s) après la fermeture", debordements).red()
+                );
+            }
+        }
+    }
+
+    pub fn menu_bloc_operatoire(&mut self) {
+        loop {
+            println!("\n{}", "=== BLOC OPÉRATOIRE ===".blue().bold());
+            println!("1. Ajouter une salle");
+            println!("2. Programmer une intervention");
+            println!("3. Programme d'une salle");
+            println!("4. Entrée en salle");
+            println!("5. Sortie de salle");
+            println!("6. Annuler une intervention");
+            println!("7. Rapport d'occupation et de dépassements");
+            println!("8. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.ajouter_salle(),
+                2 => self.programmer_intervention(),
+                3 => self.programme_salle(),
+                4 => self.pointer_intervention(true),
+                5 => self.pointer_intervention(false),
+                6 => self.annuler_intervention(),
+                7 => self.rapport_bloc(),
+                8 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
@@ -0,0 +1,600 @@
+use chrono::Duration;
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::armoires::MOTIF_ANNULATION_TRANSFERT;
+use crate::pharmacie::TypeMouvement;
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date, parse_date_heure, Application, FORMAT_DATE,
+};
+
+// Fenêtre d'observation de la consommation pour les suggestions
+const PERIODE_CONSOMMATION_JOURS: i64 = 90;
+// Nombre de jours de consommation couverts par une commande suggérée
+const COUVERTURE_JOURS: f64 = 30.0;
+// Délai retenu pour un médicament jamais commandé
+const DELAI_LIVRAISON_DEFAUT: u32 = 7;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Fournisseur {
+    id: u32,
+    nom: String,
+    contact: String,
+    delai_livraison_jours: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StatutCommande {
+    Envoyee,
+    PartiellementLivree,
+    Livree,
+    // Clôturée sans livraison complète, le reliquat est abandonné
+    Soldee,
+    Annulee,
+}
+
+impl StatutCommande {
+    fn libelle(&self) -> &'static str {
+        match self {
+            StatutCommande::Envoyee => "Envoyée",
+            StatutCommande::PartiellementLivree => "Partiellement livrée",
+            StatutCommande::Livree => "Livrée",
+            StatutCommande::Soldee => "Soldée",
+            StatutCommande::Annulee => "Annulée",
+        }
+    }
+
+    fn en_attente(&self) -> bool {
+        matches!(
+            self,
+            StatutCommande::Envoyee | StatutCommande::PartiellementLivree
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LigneCommande {
+    medicament_id: u32,
+    quantite_commandee: u32,
+    quantite_recue: u32,
+}
+
+impl LigneCommande {
+    fn reliquat(&self) -> u32 {
+        self.quantite_commandee.saturating_sub(self.quantite_recue)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CommandeFournisseur {
+    id: u32,
+    fournisseur_id: u32,
+    lignes: Vec<LigneCommande>,
+    statut: StatutCommande,
+    passee_le: String,
+    passee_par: u32,
+    livraison_prevue: String,
+}
+
+impl CommandeFournisseur {
+    fn en_retard(&self, date: chrono::NaiveDate) -> bool {
+        self.statut.en_attente() && parse_date(&self.livraison_prevue).is_some_and(|d| d < date)
+    }
+}
+
+impl Application {
+    fn nom_fournisseur(&self, fournisseur_id: u32) -> String {
+        self.pharmacie
+            .fournisseurs
+            .iter()
+            .find(|f| f.id == fournisseur_id)
+            .map(|f| f.nom.clone())
+            .unwrap_or_else(|| format!("Fournisseur #{}", fournisseur_id))
+    }
+
+    pub fn nom_medicament(&self, medicament_id: u32) -> String {
+        self.pharmacie
+            .medicaments
+            .iter()
+            .find(|m| m.id == medicament_id)
+            .map(|m| m.nom.clone())
+            .unwrap_or_else(|| format!("Médicament #{}", medicament_id))
+    }
+
+    // Quantité commandée restant à livrer
+    pub fn quantite_en_commande(&self, medicament_id: u32) -> u32 {
+        self.pharmacie
+            .commandes
+            .iter()
+            .filter(|c| c.statut.en_attente())
+            .flat_map(|c| &c.lignes)
+            .filter(|l| l.medicament_id == medicament_id)
+            .map(|l| l.reliquat())
+            .sum()
+    }
+
+    // Consommation journalière moyenne (sorties vers les services et dispensations),
+    // nette des retours de transferts annulés
+    fn consommation_journaliere(&self, medicament_id: u32) -> f64 {
+        let debut = chrono::Local::now().naive_local() - Duration::days(PERIODE_CONSOMMATION_JOURS);
+        let consomme: i64 = self
+            .pharmacie
+            .mouvements
+            .iter()
+            .filter(|m| m.medicament_id() == medicament_id && m.emplacement().is_none())
+            .filter(|m| match m.type_mouvement() {
+                TypeMouvement::SortieService(_) | TypeMouvement::Dispensation { .. } => true,
+                TypeMouvement::Retour => m.commentaire().starts_with(MOTIF_ANNULATION_TRANSFERT),
+                _ => false,
+            })
+            .filter(|m| parse_date_heure(m.date()).is_some_and(|d| d >= debut))
+            .map(|m| -m.variation())
+            .sum();
+        consomme.max(0) as f64 / PERIODE_CONSOMMATION_JOURS as f64
+    }
+
+    // Délai du fournisseur de la dernière commande passée pour ce médicament
+    fn delai_livraison(&self, medicament_id: u32) -> u32 {
+        self.pharmacie
+            .commandes
+            .iter()
+            .rev()
+            .find(|c| c.lignes.iter().any(|l| l.medicament_id == medicament_id))
+            .and_then(|c| {
+                self.pharmacie
+                    .fournisseurs
+                    .iter()
+                    .find(|f| f.id == c.fournisseur_id)
+            })
+            .map(|f| f.delai_livraison_jours)
+            .unwrap_or(DELAI_LIVRAISON_DEFAUT)
+    }
+
+    // Commande suggérée quand le stock disponible plus les reliquats ne couvre plus
+    // la consommation pendant le délai de livraison en plus du seuil d'alerte.
+    // Sans fournisseur choisi, le délai est celui du dernier fournisseur du médicament.
+    fn suggestions_reapprovisionnement(
+        &self,
+        fournisseur: Option<&Fournisseur>,
+    ) -> Vec<(u32, u32)> {
+        self.pharmacie
+            .medicaments
+            .iter()
+            .filter_map(|m| {
+                let consommation = self.consommation_journaliere(m.id);
+                let delai = fournisseur
+                    .map(|f| f.delai_livraison_jours)
+                    .unwrap_or_else(|| self.delai_livraison(m.id))
+                    as f64;
+                let position = m.stock_disponible() + self.quantite_en_commande(m.id);
+                let point_commande = (consommation * delai).ceil() as u32 + m.seuil_alerte;
+                if position > point_commande {
+                    return None;
+                }
+                // Au minimum de quoi repasser au-dessus du point de commande
+                let cible = ((consommation * (delai + COUVERTURE_JOURS)).ceil() as u32
+                    + m.seuil_alerte)
+                    .max(point_commande + 1);
+                Some((m.id, cible - position))
+            })
+            .collect()
+    }
+
+    fn ajouter_fournisseur(&mut self) {
+        println!("{}", "\n=== AJOUT D'UN FOURNISSEUR ===".green());
+        let id = (self.pharmacie.fournisseurs.len() + 1) as u32;
+        let nom = lire_chaine("Nom du fournisseur: ");
+        if nom.is_empty()
+            || self
+                .pharmacie
+                .fournisseurs
+                .iter()
+                .any(|f| f.nom.eq_ignore_ascii_case(&nom))
+        {
+            println!("{}", "Nom de fournisseur vide ou déjà utilisé!".red());
+            return;
+        }
+        let contact = lire_chaine("Contact: ");
+        let delai_livraison_jours = lire_nombre("Délai de livraison (jours): ");
+
+        self.pharmacie.fournisseurs.push(Fournisseur {
+            id,
+            nom,
+            contact,
+            delai_livraison_jours,
+        });
+        self.save_data();
+        println!("{}", "\nFournisseur ajouté avec succès!".green());
+    }
+
+    fn liste_fournisseurs(&self) {
+        println!("{}", "\n=== LISTE DES FOURNISSEURS ===".green());
+        for fournisseur in &self.pharmacie.fournisseurs {
+            println!("{}", "-".repeat(40));
+            println!("ID: {}", fournisseur.id);
+            println!("Nom: {}", fournisseur.nom);
+            println!("Contact: {}", fournisseur.contact);
+            println!(
+                "Délai de livraison: {} jour(s)",
+                fournisseur.delai_livraison_jours
+            );
+        }
+    }
+
+    fn creer_commande(
+        &mut self,
+        fournisseur_id: u32,
+        lignes: Vec<LigneCommande>,
+    ) -> Result<u32, String> {
+        let delai = self
+            .pharmacie
+            .fournisseurs
+            .iter()
+            .find(|f| f.id == fournisseur_id)
+            .map(|f| f.delai_livraison_jours)
+            .ok_or("Fournisseur non trouvé!")?;
+        if lignes.is_empty() {
+            return Err("Une commande doit comporter au moins une ligne!".to_string());
+        }
+        let prevue = chrono::Local::now().date_naive() + Duration::days(delai as i64);
+        let saisie = lire_chaine(&format!(
+            "Livraison prévue (JJ/MM/AAAA, Entrée pour le {}): ",
+            prevue.format(FORMAT_DATE)
+        ));
+        let livraison_prevue = if saisie.is_empty() {
+            prevue.format(FORMAT_DATE).to_string()
+        } else if parse_date(&saisie).is_some() {
+            saisie
+        } else {
+            return Err("Date invalide!".to_string());
+        };
+        let passee_par = self.identifier_utilisateur()?;
+        let id = (self.pharmacie.commandes.len() + 1) as u32;
+        self.pharmacie.commandes.push(CommandeFournisseur {
+            id,
+            fournisseur_id,
+            lignes,
+            statut: StatutCommande::Envoyee,
+            passee_le: maintenant(),
+            passee_par,
+            livraison_prevue,
+        });
+        Ok(id)
+    }
+
+    fn passer_commande(&mut self) {
+        println!("{}", "\n=== NOUVELLE COMMANDE ===".green());
+        self.liste_fournisseurs();
+        let fournisseur_id = lire_nombre("ID du fournisseur: ");
+        self.verifier_stock();
+        let mut lignes: Vec<LigneCommande> = Vec::new();
+        loop {
+            let medicament_id = lire_nombre("ID du médicament (0 pour terminer): ");
+            if medicament_id == 0 {
+                break;
+            }
+            if !self
+                .pharmacie
+                .medicaments
+                .iter()
+                .any(|m| m.id == medicament_id)
+            {
+                println!("{}", "Médicament non trouvé!".red());
+                continue;
+            }
+            let quantite = lire_nombre("Quantité commandée: ");
+            if quantite == 0 {
+                continue;
+            }
+            match lignes.iter_mut().find(|l| l.medicament_id == medicament_id) {
+                Some(ligne) => ligne.quantite_commandee += quantite,
+                None => lignes.push(LigneCommande {
+                    medicament_id,
+                    quantite_commandee: quantite,
+                    quantite_recue: 0,
+                }),
+            }
+        }
+        match self.creer_commande(fournisseur_id, lignes) {
+            Ok(id) => {
+                self.save_data();
+                println!("{}", format!("\nCommande #{} enregistrée!", id).green());
+            }
+            Err(erreur) => println!("{}", erreur.red()),
+        }
+    }
+
+    fn afficher_commande(&self, commande: &CommandeFournisseur) {
+        let aujourd_hui = chrono::Local::now().date_naive();
+        println!("{}", "-".repeat(40));
+        println!(
+            "Commande #{} - {} ({})",
+            commande.id,
+            self.nom_fournisseur(commande.fournisseur_id),
+            commande.statut.libelle()
+        );
+        println!(
+            "Passée le {} par {}",
+            commande.passee_le,
+            self.nom_utilisateur(Some(commande.passee_par))
+        );
+        let livraison = format!("Livraison prévue: {}", commande.livraison_prevue);
+        if commande.en_retard(aujourd_hui) {
+            println!("{}", format!("{} (EN RETARD)", livraison).red());
+        } else {
+            println!("{}", livraison);
+        }
+        for ligne in &commande.lignes {
+            println!(
+                "  {} : {} commandé(s), {} reçu(s)",
+                self.nom_medicament(ligne.medicament_id),
+                ligne.quantite_commandee,
+                ligne.quantite_recue
+            );
+        }
+    }
+
+    fn liste_commandes(&self) {
+        println!("{}", "\n=== COMMANDES FOURNISSEURS ===".green());
+        for commande in &self.pharmacie.commandes {
+            self.afficher_commande(commande);
+        }
+    }
+
+    // Chaque quantité reçue entre en stock dans un lot au nom du fournisseur
+    fn receptionner_commande(&mut self) {
+        println!("{}", "\n=== RÉCEPTION D'UNE LIVRAISON ===".green());
+        let commande_id = lire_nombre("ID de la commande: ");
+        let Some(commande) = self
+            .pharmacie
+            .commandes
+            .iter()
+            .find(|c| c.id == commande_id && c.statut.en_attente())
+            .cloned()
+        else {
+            println!("{}", "Commande non trouvée ou déjà clôturée!".red());
+            return;
+        };
+        self.afficher_commande(&commande);
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        let fournisseur = self.nom_fournisseur(commande.fournisseur_id);
+
+        for (index, ligne) in commande.lignes.iter().enumerate() {
+            if ligne.reliquat() == 0 {
+                continue;
+            }
+            let nom = self.nom_medicament(ligne.medicament_id);
+            println!("\n{} (reste à livrer: {})", nom, ligne.reliquat());
+            let quantite = lire_nombre("Quantité reçue (0 si non livrée): ");
+            if quantite == 0 {
+                continue;
+            }
+            if quantite > ligne.reliquat() {
+                println!(
+                    "{}",
+                    "Quantité supérieure au reste à livrer, ligne ignorée!".red()
+                );
+                continue;
+            }
+            let resultat = self
+                .preparer_lot_entrant(ligne.medicament_id, Some(&fournisseur))
+                .and_then(|lot| {
+                    self.enregistrer_mouvement(
+                        ligne.medicament_id,
+                        TypeMouvement::Entree,
+                        quantite,
+                        Some(&lot),
+                        Some(utilisateur_id),
+                        format!("Réception commande #{}", commande.id),
+                    )
+                });
+            match resultat {
+                Ok(()) => {
+                    if let Some(commande) = self
+                        .pharmacie
+                        .commandes
+                        .iter_mut()
+                        .find(|c| c.id == commande_id)
+                    {
+                        commande.lignes[index].quantite_recue += quantite;
+                    }
+                }
+                Err(erreur) => println!("{}", erreur.red()),
+            }
+        }
+
+        if let Some(commande) = self
+            .pharmacie
+            .commandes
+            .iter_mut()
+            .find(|c| c.id == commande_id)
+        {
+            if commande.lignes.iter().all(|l| l.reliquat() == 0) {
+                commande.statut = StatutCommande::Livree;
+            } else if commande.lignes.iter().any(|l| l.quantite_recue > 0) {
+                commande.statut = StatutCommande::PartiellementLivree;
+            }
+            println!(
+                "{}",
+                format!("\nCommande #{}: {}", commande.id, commande.statut.libelle()).green()
+            );
+        }
+        self.save_data();
+    }
+
+    fn liste_reliquats(&self) {
+        println!("{}", "\n=== RELIQUATS DE COMMANDE ===".green());
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let mut vide = true;
+        for commande in self
+            .pharmacie
+            .commandes
+            .iter()
+            .filter(|c| c.statut.en_attente())
+        {
+            for ligne in commande.lignes.iter().filter(|l| l.reliquat() > 0) {
+                vide = false;
+                let texte = format!(
+                    "Commande #{} ({}) - {} : {} à livrer, prévu le {}",
+                    commande.id,
+                    self.nom_fournisseur(commande.fournisseur_id),
+                    self.nom_medicament(ligne.medicament_id),
+                    ligne.reliquat(),
+                    commande.livraison_prevue
+                );
+                if commande.en_retard(aujourd_hui) {
+                    println!("{}", format!("{} (EN RETARD)", texte).red());
+                } else {
+                    println!("{}", texte);
+                }
+            }
+        }
+        if vide {
+            println!("Aucun reliquat en attente.");
+        }
+    }
+
+    // Une commande jamais livrée est annulée; partiellement livrée, son reliquat est soldé
+    fn cloturer_commande(&mut self) {
+        println!("{}", "\n=== CLÔTURE D'UNE COMMANDE ===".green());
+        let commande_id = lire_nombre("ID de la commande: ");
+        let Some(commande) = self
+            .pharmacie
+            .commandes
+            .iter_mut()
+            .find(|c| c.id == commande_id && c.statut.en_attente())
+        else {
+            println!("{}", "Commande non trouvée ou déjà clôturée!".red());
+            return;
+        };
+        commande.statut = if commande.statut == StatutCommande::Envoyee {
+            StatutCommande::Annulee
+        } else {
+            StatutCommande::Soldee
+        };
+        println!(
+            "{}",
+            format!("\nCommande #{}: {}", commande.id, commande.statut.libelle()).green()
+        );
+        self.save_data();
+    }
+
+    fn afficher_suggestions(&mut self) {
+        println!("{}", "\n=== SUGGESTIONS DE RÉAPPROVISIONNEMENT ===".green());
+        // Les quantités dépendent du délai de livraison du fournisseur retenu
+        let fournisseur = if self.pharmacie.fournisseurs.is_empty() {
+            println!(
+                "{}",
+                "Aucun fournisseur: quantités calculées avec les délais habituels.".yellow()
+            );
+            None
+        } else {
+            self.liste_fournisseurs();
+            let fournisseur_id = lire_nombre("ID du fournisseur: ");
+            match self
+                .pharmacie
+                .fournisseurs
+                .iter()
+                .find(|f| f.id == fournisseur_id)
+            {
+                Some(fournisseur) => Some(fournisseur.clone()),
+                None => {
+                    println!("{}", "Fournisseur non trouvé!".red());
+                    return;
+                }
+            }
+        };
+        let suggestions = self.suggestions_reapprovisionnement(fournisseur.as_ref());
+        if suggestions.is_empty() {
+            println!("Aucun réapprovisionnement nécessaire.");
+            return;
+        }
+        for (medicament_id, quantite) in &suggestions {
+            let Some(medicament) = self
+                .pharmacie
+                .medicaments
+                .iter()
+                .find(|m| m.id == *medicament_id)
+            else {
+                continue;
+            };
+            println!("{}", "-".repeat(40));
+            println!("{} (ID {})", medicament.nom, medicament.id);
+            println!(
+                "Disponible: {}, en commande: {}, seuil: {}",
+                medicament.stock_disponible(),
+                self.quantite_en_commande(medicament.id),
+                medicament.seuil_alerte
+            );
+            println!(
+                "Consommation: {:.1}/jour, délai de livraison: {} jour(s)",
+                self.consommation_journaliere(medicament.id),
+                fournisseur
+                    .as_ref()
+                    .map(|f| f.delai_livraison_jours)
+                    .unwrap_or_else(|| self.delai_livraison(medicament.id))
+            );
+            println!("{}", format!("Quantité suggérée: {}", quantite).yellow());
+        }
+
+        let Some(fournisseur) = fournisseur else {
+            return;
+        };
+        let reponse = lire_chaine(&format!(
+            "\nCommander ces quantités auprès de {}? (o/n): ",
+            fournisseur.nom
+        ));
+        if !reponse.eq_ignore_ascii_case("o") {
+            return;
+        }
+        let lignes = suggestions
+            .into_iter()
+            .map(|(medicament_id, quantite)| LigneCommande {
+                medicament_id,
+                quantite_commandee: quantite,
+                quantite_recue: 0,
+            })
+            .collect();
+        match self.creer_commande(fournisseur.id, lignes) {
+            Ok(id) => {
+                self.save_data();
+                println!("{}", format!("\nCommande #{} enregistrée!", id).green());
+            }
+            Err(erreur) => println!("{}", erreur.red()),
+        }
+    }
+
+    pub fn menu_commandes(&mut self) {
+        loop {
+            println!("\n{}", "=== FOURNISSEURS ET COMMANDES ===".blue().bold());
+            println!("1. Ajouter un fournisseur");
+            println!("2. Liste des fournisseurs");
+            println!("3. Passer une commande");
+            println!("4. Liste des commandes");
+            println!("5. Réceptionner une livraison");
+            println!("6. Reliquats en attente");
+            println!("7. Clôturer une commande");
+            println!("8. Suggestions de réapprovisionnement");
+            println!("9. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.ajouter_fournisseur(),
+                2 => self.liste_fournisseurs(),
+                3 => self.passer_commande(),
+                4 => self.liste_commandes(),
+                5 => self.receptionner_commande(),
+                6 => self.liste_reliquats(),
+                7 => self.cloturer_commande(),
+                8 => self.afficher_suggestions(),
+                9 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::fs;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use colored::*;
+
+use crate::planning::{nom_jour, parse_heure};
+use crate::{lire_chaine, lire_nombre, parse_date, Application, Personnel, FORMAT_DATE};
+
+const MAX_HEURES_SEMAINE: i64 = 48;
+const REPOS_MINIMUM_HEURES: i64 = 11;
+const MAX_NUITS_CONSECUTIVES: usize = 3;
+
+// Une garde est une nuit de 20h00 au lendemain 8h00
+const HEURE_DEBUT_GARDE: u32 = 20;
+const DUREE_GARDE_HEURES: i64 = 12;
+
+pub struct Vacation {
+    pub debut: NaiveDateTime,
+    pub fin: NaiveDateTime,
+    pub garde: bool,
+}
+
+enum Infraction {
+    DureeHebdomadaire {
+        lundi: NaiveDate,
+        minutes: i64,
+    },
+    ReposInsuffisant {
+        fin: NaiveDateTime,
+        reprise: NaiveDateTime,
+    },
+    NuitsConsecutives {
+        debut: NaiveDate,
+        nombre: usize,
+    },
+}
+
+impl Infraction {
+    fn description(&self) -> String {
+        match self {
+            Infraction::DureeHebdomadaire { lundi, minutes } => format!(
+                "Semaine du {}: {}h{:02} travaillées (maximum {}h)",
+                lundi.format(FORMAT_DATE),
+                minutes / 60,
+                minutes % 60,
+                MAX_HEURES_SEMAINE
+            ),
+            Infraction::ReposInsuffisant { fin, reprise } => {
+                let repos = (*reprise - *fin).num_minutes();
+                format!(
+                    "Repos de {}h{:02} entre le {} et le {} (minimum {}h)",
+                    repos / 60,
+                    repos % 60,
+                    fin.format("%d/%m/%Y %H:%M"),
+                    reprise.format("%d/%m/%Y %H:%M"),
+                    REPOS_MINIMUM_HEURES
+                )
+            }
+            Infraction::NuitsConsecutives { debut, nombre } => format!(
+                "{} nuits consécutives à partir du {} (maximum {})",
+                nombre,
+                debut.format(FORMAT_DATE),
+                MAX_NUITS_CONSECUTIVES
+            ),
+        }
+    }
+}
+
+// Vacations d'un membre entre deux dates incluses: horaires hebdomadaires
+// hors congés, plus les gardes de nuit
+pub fn vacations(membre: &Personnel, du: NaiveDate, au: NaiveDate) -> Vec<Vacation> {
+    let mut vacations = Vec::new();
+    for date in du.iter_days().take_while(|d| *d <= au) {
+        if membre.planning.en_conge(date) {
+            continue;
+        }
+        for horaire in membre
+            .planning
+            .horaires
+            .iter()
+            .filter(|h| h.jour == nom_jour(date.weekday()))
+        {
+            if let (Some(debut), Some(fin)) =
+                (parse_heure(&horaire.debut), parse_heure(&horaire.fin))
+            {
+                vacations.push(Vacation {
+                    debut: date.and_time(debut),
+                    fin: date.and_time(fin),
+                    garde: false,
+                });
+            }
+        }
+    }
+    for date in membre
+        .planning
+        .gardes
+        .iter()
+        .filter_map(|g| parse_date(&g.date))
+    {
+        if date < du || date > au {
+            continue;
+        }
+        let debut = date.and_time(NaiveTime::from_hms_opt(HEURE_DEBUT_GARDE, 0, 0).unwrap());
+        vacations.push(Vacation {
+            debut,
+            fin: debut + Duration::hours(DUREE_GARDE_HEURES),
+            garde: true,
+        });
+    }
+    vacations.sort_by_key(|v| v.debut);
+    vacations
+}
+
+// Contrôle un membre sur un mois: durée hebdomadaire, repos quotidien
+// et enchaînement de nuits. Les semaines à cheval sur le mois sont
+// évaluées en entier.
+fn controler_membre(membre: &Personnel, annee: i32, mois: u32) -> Vec<Infraction> {
+    let premier = match NaiveDate::from_ymd_opt(annee, mois, 1) {
+        Some(date) => date,
+        None => return Vec::new(),
+    };
+    let dernier = premier
+        .iter_days()
+        .take_while(|d| d.month() == mois)
+        .last()
+        .unwrap_or(premier);
+    let lundi = premier - Duration::days(premier.weekday().num_days_from_monday() as i64);
+    let dimanche = dernier + Duration::days(6 - dernier.weekday().num_days_from_monday() as i64);
+    let vacations = vacations(membre, lundi - Duration::days(1), dimanche);
+    let mut infractions = Vec::new();
+
+    let mut semaine = lundi;
+    while semaine <= dernier {
+        let debut = semaine.and_hms_opt(0, 0, 0).unwrap();
+        let fin = debut + Duration::days(7);
+        let minutes: i64 = vacations
+            .iter()
+            .map(|v| (v.fin.min(fin) - v.debut.max(debut)).num_minutes().max(0))
+            .sum();
+        if minutes > MAX_HEURES_SEMAINE * 60 {
+            infractions.push(Infraction::DureeHebdomadaire {
+                lundi: semaine,
+                minutes,
+            });
+        }
+        semaine += Duration::days(7);
+    }
+
+    // Les vacations d'une même journée forment une seule amplitude
+    let mut amplitudes: Vec<(NaiveDateTime, NaiveDateTime)> = Vec::new();
+    for vacation in &vacations {
+        match amplitudes.last_mut() {
+            Some((debut, fin)) if debut.date() == vacation.debut.date() => {
+                *fin = (*fin).max(vacation.fin);
+            }
+            _ => amplitudes.push((vacation.debut, vacation.fin)),
+        }
+    }
+    for paire in amplitudes.windows(2) {
+        let (fin, reprise) = (paire[0].1, paire[1].0);
+        let dans_le_mois = reprise.date() >= premier && reprise.date() <= dernier;
+        if dans_le_mois && reprise - fin < Duration::hours(REPOS_MINIMUM_HEURES) {
+            infractions.push(Infraction::ReposInsuffisant { fin, reprise });
+        }
+    }
+
+    let mut nuits: Vec<NaiveDate> = vacations
+        .iter()
+        .filter(|v| v.garde)
+        .map(|v| v.debut.date())
+        .collect();
+    nuits.dedup();
+    let mut i = 0;
+    while i < nuits.len() {
+        let mut j = i + 1;
+        while j < nuits.len() && nuits[j] - nuits[j - 1] == Duration::days(1) {
+            j += 1;
+        }
+        let touche_le_mois = nuits[i] <= dernier && nuits[j - 1] >= premier;
+        if j - i > MAX_NUITS_CONSECUTIVES && touche_le_mois {
+            infractions.push(Infraction::NuitsConsecutives {
+                debut: nuits[i],
+                nombre: j - i,
+            });
+        }
+        i = j;
+    }
+    infractions
+}
+
+impl Application {
+    fn rapport_conformite(&self, service_id: u32, annee: i32, mois: u32) -> Option<Vec<String>> {
+        let service = self.services.iter().find(|s| s.id == service_id)?;
+        NaiveDate::from_ymd_opt(annee, mois, 1)?;
+
+        let mut lignes = vec![
+            format!(
+                "Rapport de conformité du temps de travail - {} - {:02}/{}",
+                service.nom, mois, annee
+            ),
+            format!(
+                "Règles: {}h maximum par semaine, {}h de repos entre deux amplitudes, {} nuits consécutives au plus",
+                MAX_HEURES_SEMAINE, REPOS_MINIMUM_HEURES, MAX_NUITS_CONSECUTIVES
+            ),
+        ];
+        let mut conformes = 0;
+        let mut total = 0;
+        // Les membres en arrêt ou suspendus restent contrôlés sur le mois écoulé
+        for membre in self
+            .personnel
+            .iter()
+            .filter(|p| p.actif() && self.services_du_membre(p.id).contains(&service_id))
+        {
+            let infractions = controler_membre(membre, annee, mois);
+            lignes.push("-".repeat(40));
+            lignes.push(format!(
+                "{} {} ({})",
+                membre.nom, membre.prenom, membre.specialite
+            ));
+            if infractions.is_empty() {
+                lignes.push("  Conforme".to_string());
+                conformes += 1;
+            }
+            for infraction in &infractions {
+                lignes.push(format!("  {}", infraction.description()));
+            }
+            total += infractions.len();
+        }
+        lignes.push("-".repeat(40));
+        lignes.push(format!(
+            "{} membre(s) conforme(s), {} infraction(s) relevée(s)",
+            conformes, total
+        ));
+        Some(lignes)
+    }
+
+    pub fn controle_conformite(&self) {
+        println!("{}", "\n=== CONFORMITÉ DU TEMPS DE TRAVAIL ===".green());
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        let periode = lire_chaine("Mois (MM/AAAA): ");
+        let (mois, annee) = match periode
+            .split_once('/')
+            .and_then(|(m, a)| Some((m.parse::<u32>().ok()?, a.parse::<i32>().ok()?)))
+        {
+            Some(periode) => periode,
+            None => {
+                println!("{}", "Mois invalide!".red());
+                return;
+            }
+        };
+
+        let lignes = match self.rapport_conformite(service_id, annee, mois) {
+            Some(lignes) => lignes,
+            None => {
+                println!("{}", "Service ou mois invalide!".red());
+                return;
+            }
+        };
+        for ligne in &lignes {
+            if ligne.starts_with("  ") && ligne.trim() != "Conforme" {
+                println!("{}", ligne.red());
+            } else {
+                println!("{}", ligne);
+            }
+        }
+
+        let nom_fichier = format!("conformite-{}-{:02}-{}.txt", service_id, mois, annee);
+        let saisie = lire_chaine(&format!(
+            "\nFichier pour les RH (Entrée pour {}, - pour ne pas enregistrer): ",
+            nom_fichier
+        ));
+        if saisie == "-" {
+            return;
+        }
+        let chemin = if saisie.is_empty() {
+            nom_fichier
+        } else {
+            saisie
+        };
+        match fs::write(&chemin, lignes.join("\n") + "\n") {
+            Ok(()) => println!("{}", format!("Rapport enregistré dans {}", chemin).green()),
+            Err(erreur) => println!("{}", format!("Erreur d'écriture: {}", erreur).red()),
+        }
+    }
+}
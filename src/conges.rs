@@ -0,0 +1,336 @@
+use chrono::{Datelike, NaiveDate};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::planning::nom_jour;
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date, Application, Horaire, Periode,
+    StatutRendezVous,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum TypeConge {
+    CongePaye,
+    Maladie,
+    Formation,
+    Rtt,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum StatutDemande {
+    EnAttente,
+    Approuvee,
+    Refusee,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Decision {
+    par: u32,
+    date: String,
+    commentaire: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DemandeConge {
+    id: u32,
+    personnel_id: u32,
+    type_conge: TypeConge,
+    debut: String,
+    fin: String,
+    motif: String,
+    soumise_le: String,
+    statut: StatutDemande,
+    decision: Option<Decision>,
+}
+
+impl DemandeConge {
+    fn jours(&self) -> Vec<NaiveDate> {
+        match (parse_date(&self.debut), parse_date(&self.fin)) {
+            (Some(debut), Some(fin)) => debut.iter_days().take_while(|d| *d <= fin).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+fn travaille_dans(horaire: &Horaire, service: &str, date: NaiveDate) -> bool {
+    horaire.service == service && horaire.jour == nom_jour(date.weekday())
+}
+
+impl Application {
+    // Services d'un membre du personnel: affectation ou horaires qui y sont rattachés
+    pub fn services_du_membre(&self, personnel_id: u32) -> Vec<u32> {
+        let membre = self.personnel.iter().find(|p| p.id == personnel_id);
+        self.services
+            .iter()
+            .filter(|s| {
+                s.personnel_affecte.contains(&personnel_id)
+                    || membre
+                        .is_some_and(|m| m.planning.horaires.iter().any(|h| h.service == s.nom))
+            })
+            .map(|s| s.id)
+            .collect()
+    }
+
+    // Nombre de personnes présentes dans un service un jour donné, sans compter `exclure`
+    fn effectif_present(&self, service_id: u32, date: NaiveDate, exclure: u32) -> u32 {
+        let nom = match self.services.iter().find(|s| s.id == service_id) {
+            Some(service) => &service.nom,
+            None => return 0,
+        };
+        self.personnel
+            .iter()
+            .filter(|p| p.id != exclure && p.disponible_le(date) && !p.planning.en_conge(date))
+            .filter(|p| {
+                p.planning
+                    .horaires
+                    .iter()
+                    .any(|h| travaille_dans(h, nom, date))
+            })
+            .count() as u32
+    }
+
+    // Jours où le demandeur est attendu dans un service et où son absence ferait
+    // passer ce service sous son effectif minimum
+    fn jours_sous_effectif(&self, demande: &DemandeConge) -> Vec<(String, NaiveDate)> {
+        let horaires = match self.personnel.iter().find(|p| p.id == demande.personnel_id) {
+            Some(membre) => &membre.planning.horaires,
+            None => return Vec::new(),
+        };
+        let mut manques = Vec::new();
+        for service_id in self.services_du_membre(demande.personnel_id) {
+            let service = match self.services.iter().find(|s| s.id == service_id) {
+                Some(service) => service,
+                None => continue,
+            };
+            for date in demande.jours() {
+                if !horaires
+                    .iter()
+                    .any(|h| travaille_dans(h, &service.nom, date))
+                {
+                    continue;
+                }
+                if self.effectif_present(service_id, date, demande.personnel_id)
+                    < service.effectif_minimum
+                {
+                    manques.push((service.nom.clone(), date));
+                }
+            }
+        }
+        manques
+    }
+
+    pub fn soumettre_demande_conge(&mut self) {
+        println!("{}", "\n=== DEMANDE DE CONGÉ ===".green());
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let personnel_id = self.personnel[index].id;
+
+        println!("Type de congé:");
+        println!("1. Congé payé");
+        println!("2. Maladie");
+        println!("3. Formation");
+        println!("4. RTT");
+        let type_conge = match lire_nombre("Choix: ") {
+            1 => TypeConge::CongePaye,
+            2 => TypeConge::Maladie,
+            3 => TypeConge::Formation,
+            4 => TypeConge::Rtt,
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        let debut = lire_chaine("Premier jour (JJ/MM/AAAA): ");
+        let fin = lire_chaine("Dernier jour (JJ/MM/AAAA): ");
+        match (parse_date(&debut), parse_date(&fin)) {
+            (Some(d), Some(f)) if d <= f => {}
+            _ => {
+                println!("{}", "Période invalide!".red());
+                return;
+            }
+        }
+        let motif = lire_chaine("Motif (facultatif): ");
+
+        self.demandes_conge.push(DemandeConge {
+            id: (self.demandes_conge.len() + 1) as u32,
+            personnel_id,
+            type_conge,
+            debut,
+            fin,
+            motif,
+            soumise_le: maintenant(),
+            statut: StatutDemande::EnAttente,
+            decision: None,
+        });
+        self.save_data();
+        println!("{}", "\nDemande de congé soumise!".green());
+    }
+
+    pub fn liste_demandes_conge(&self) {
+        println!("{}", "\n=== DEMANDES DE CONGÉ ===".green());
+        for demande in &self.demandes_conge {
+            println!("{}", "-".repeat(40));
+            println!("Demande N°{} ({:?})", demande.id, demande.type_conge);
+            if let Some(membre) = self.personnel.iter().find(|p| p.id == demande.personnel_id) {
+                println!("Demandeur: {} {}", membre.nom, membre.prenom);
+            }
+            println!("Du {} au {}", demande.debut, demande.fin);
+            if !demande.motif.is_empty() {
+                println!("Motif: {}", demande.motif);
+            }
+            println!("Soumise le: {}", demande.soumise_le);
+            match demande.statut {
+                StatutDemande::EnAttente => println!("{}", "Statut: En attente".yellow()),
+                StatutDemande::Approuvee => println!("{}", "Statut: Approuvée".green()),
+                StatutDemande::Refusee => println!("{}", "Statut: Refusée".red()),
+            }
+            if let Some(decision) = &demande.decision {
+                if let Some(chef) = self.personnel.iter().find(|p| p.id == decision.par) {
+                    println!(
+                        "Décision du {} par Dr. {} {}: {}",
+                        decision.date, chef.nom, chef.prenom, decision.commentaire
+                    );
+                }
+            }
+        }
+    }
+
+    // Marque les rendez-vous du membre tombant pendant son congé
+    fn marquer_rendez_vous_a_replanifier(&mut self, demande: &DemandeConge) -> usize {
+        let jours = demande.jours();
+        let mut marques = 0;
+        for rdv in self.rendez_vous.iter_mut().filter(|r| {
+            r.personnel_id == demande.personnel_id
+                && matches!(
+                    r.statut,
+                    StatutRendezVous::Planifie | StatutRendezVous::Confirme
+                )
+        }) {
+            if parse_date(&rdv.date).is_some_and(|d| jours.contains(&d)) {
+                rdv.a_replanifier = true;
+                marques += 1;
+                println!(
+                    "{}",
+                    format!(
+                        "RDV N°{} du {} à {} à replanifier",
+                        rdv.id, rdv.date, rdv.heure
+                    )
+                    .yellow()
+                );
+            }
+        }
+        marques
+    }
+
+    pub fn examiner_demande_conge(&mut self) {
+        println!("{}", "\n=== EXAMEN D'UNE DEMANDE DE CONGÉ ===".green());
+        self.liste_demandes_conge();
+        let demande_id = lire_nombre("ID de la demande: ");
+        let demande = match self
+            .demandes_conge
+            .iter()
+            .find(|d| d.id == demande_id && d.statut == StatutDemande::EnAttente)
+        {
+            Some(demande) => demande.clone(),
+            None => {
+                println!("{}", "Demande en attente non trouvée!".red());
+                return;
+            }
+        };
+
+        let chef_id = lire_nombre("ID du chef de service qui statue: ");
+        if chef_id == demande.personnel_id {
+            println!(
+                "{}",
+                "Un chef de service ne peut pas statuer sur sa propre demande!".red()
+            );
+            return;
+        }
+        let services = self.services_du_membre(demande.personnel_id);
+        if !self
+            .services
+            .iter()
+            .any(|s| services.contains(&s.id) && s.chef_service == chef_id)
+        {
+            println!(
+                "{}",
+                "Seul le chef d'un service du demandeur peut statuer!".red()
+            );
+            return;
+        }
+
+        println!("1. Approuver");
+        println!("2. Refuser");
+        let approuver = match lire_nombre("Choix: ") {
+            1 => true,
+            2 => false,
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        if approuver {
+            let manques = self.jours_sous_effectif(&demande);
+            if !manques.is_empty() {
+                println!("{}", "Effectif minimum non respecté:".red());
+                for (service, date) in manques {
+                    println!("  {} le {}", service, date.format("%d/%m/%Y"));
+                }
+                return;
+            }
+        }
+        let commentaire = lire_chaine("Commentaire: ");
+
+        if let Some(d) = self.demandes_conge.iter_mut().find(|d| d.id == demande_id) {
+            d.statut = if approuver {
+                StatutDemande::Approuvee
+            } else {
+                StatutDemande::Refusee
+            };
+            d.decision = Some(Decision {
+                par: chef_id,
+                date: maintenant(),
+                commentaire,
+            });
+        }
+
+        if approuver {
+            if let Some(membre) = self
+                .personnel
+                .iter_mut()
+                .find(|p| p.id == demande.personnel_id)
+            {
+                membre.planning.conges.push(Periode {
+                    debut: demande.debut.clone(),
+                    fin: demande.fin.clone(),
+                });
+            }
+            let marques = self.marquer_rendez_vous_a_replanifier(&demande);
+            println!("{} rendez-vous à replanifier.", marques);
+        }
+        self.save_data();
+        println!("{}", "\nDécision enregistrée!".green());
+    }
+
+    pub fn menu_demandes_conge(&mut self) {
+        loop {
+            println!("\n{}", "=== DEMANDES DE CONGÉ ===".blue().bold());
+            println!("1. Soumettre une demande");
+            println!("2. Liste des demandes");
+            println!("3. Approuver ou refuser une demande");
+            println!("4. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.soumettre_demande_conge(),
+                2 => self.liste_demandes_conge(),
+                3 => self.examiner_demande_conge(),
+                4 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
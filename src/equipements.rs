@@ -0,0 +1,452 @@
+use chrono::{Duration, NaiveDate};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date, Application, Equipement, StatutEquipement,
+    FORMAT_DATE,
+};
+
+// Horizon des maintenances « à venir » dans la liste des échéances
+const HORIZON_MAINTENANCE_JOURS: i64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum TypeIntervention {
+    Preventive,
+    Corrective,
+    ChangementStatut,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InterventionMaintenance {
+    date: String,
+    type_intervention: TypeIntervention,
+    intervenant: String,
+    description: String,
+    saisie_le: String,
+}
+
+impl StatutEquipement {
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            StatutEquipement::Fonctionnel => "Fonctionnel",
+            StatutEquipement::EnMaintenance => "En maintenance",
+            StatutEquipement::HorsService => "Hors service",
+        }
+    }
+}
+
+impl Equipement {
+    pub fn prochaine_maintenance(&self) -> Option<NaiveDate> {
+        parse_date(&self.prochaine_maintenance)
+    }
+
+    // Une maintenance en retard n'a de sens que pour un appareil encore utilisé
+    pub fn maintenance_en_retard(&self, aujourdhui: NaiveDate) -> bool {
+        self.statut != StatutEquipement::HorsService
+            && self.prochaine_maintenance().is_some_and(|d| d < aujourdhui)
+    }
+
+    // Le changement est tracé dans le journal de l'équipement
+    pub fn changer_statut(&mut self, nouveau: StatutEquipement, motif: &str) {
+        self.interventions.push(InterventionMaintenance {
+            date: chrono::Local::now().format(FORMAT_DATE).to_string(),
+            type_intervention: TypeIntervention::ChangementStatut,
+            intervenant: String::new(),
+            description: format!(
+                "{} -> {}: {}",
+                self.statut.libelle(),
+                nouveau.libelle(),
+                motif
+            ),
+            saisie_le: maintenant(),
+        });
+        self.statut = nouveau;
+    }
+
+    pub fn journaliser_reparation(&mut self, intervenant: &str, description: &str) {
+        self.interventions.push(InterventionMaintenance {
+            date: chrono::Local::now().format(FORMAT_DATE).to_string(),
+            type_intervention: TypeIntervention::Corrective,
+            intervenant: intervenant.to_string(),
+            description: description.to_string(),
+            saisie_le: maintenant(),
+        });
+    }
+
+    fn planifier_prochaine(&mut self, depuis: NaiveDate) {
+        if self.intervalle_maintenance > 0 {
+            self.prochaine_maintenance = (depuis
+                + Duration::days(self.intervalle_maintenance as i64))
+            .format(FORMAT_DATE)
+            .to_string();
+        }
+    }
+}
+
+impl Application {
+    pub fn equipement_mut(
+        &mut self,
+        service_id: u32,
+        equipement_id: u32,
+    ) -> Option<&mut Equipement> {
+        self.services
+            .iter_mut()
+            .find(|s| s.id == service_id)
+            .and_then(|s| s.equipements.iter_mut().find(|e| e.id == equipement_id))
+    }
+
+    pub fn choisir_equipement(&self) -> Option<(u32, u32)> {
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        let service = match self.services.iter().find(|s| s.id == service_id) {
+            Some(service) => service,
+            None => {
+                println!("{}", "Service non trouvé!".red());
+                return None;
+            }
+        };
+        for equipement in &service.equipements {
+            println!(
+                "{}. {} {} - {} (habilitation: {})",
+                equipement.id,
+                equipement.nom,
+                equipement.modele,
+                equipement.statut.libelle(),
+                equipement
+                    .qualification_requise
+                    .as_deref()
+                    .unwrap_or("aucune")
+            );
+        }
+        let equipement_id = lire_nombre("ID de l'équipement: ");
+        if !service.equipements.iter().any(|e| e.id == equipement_id) {
+            println!("{}", "Équipement non trouvé!".red());
+            return None;
+        }
+        Some((service_id, equipement_id))
+    }
+
+    fn ajouter_equipement(&mut self) {
+        println!("{}", "\n=== ENREGISTREMENT D'UN ÉQUIPEMENT ===".green());
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        if !self
+            .services
+            .iter()
+            .any(|s| s.id == service_id && s.actif())
+        {
+            println!("{}", "Service non trouvé ou désactivé!".red());
+            return;
+        }
+
+        // Identifiant unique sur l'ensemble des services
+        let id = (self
+            .services
+            .iter()
+            .map(|s| s.equipements.len())
+            .sum::<usize>()
+            + 1) as u32;
+        let nom = lire_chaine("Nom de l'équipement: ");
+        let modele = lire_chaine("Modèle: ");
+        let mise_en_service =
+            lire_chaine("Date de dernière maintenance ou de mise en service (JJ/MM/AAAA): ");
+        let depuis = match parse_date(&mise_en_service) {
+            Some(date) => date,
+            None => {
+                println!("{}", "Date invalide!".red());
+                return;
+            }
+        };
+        let intervalle_maintenance =
+            lire_nombre("Intervalle de maintenance préventive en jours (0 si aucun): ");
+
+        let mut equipement = Equipement {
+            id,
+            nom,
+            statut: StatutEquipement::Fonctionnel,
+            derniere_maintenance: mise_en_service,
+            prochaine_maintenance: String::new(),
+            qualification_requise: None,
+            modele,
+            intervalle_maintenance,
+            interventions: Vec::new(),
+        };
+        equipement.planifier_prochaine(depuis);
+
+        if let Some(service) = self.services.iter_mut().find(|s| s.id == service_id) {
+            service.equipements.push(equipement);
+        }
+        self.save_data();
+        println!("{}", format!("\nÉquipement N°{} enregistré!", id).green());
+    }
+
+    fn inventaire_equipements(&self) {
+        println!("{}", "\n=== INVENTAIRE DES ÉQUIPEMENTS ===".green());
+        let aujourdhui = chrono::Local::now().date_naive();
+        for service in &self.services {
+            if service.equipements.is_empty() {
+                continue;
+            }
+            println!("{}", format!("\n--- {} ---", service.nom).bold());
+            for equipement in &service.equipements {
+                println!("{}", "-".repeat(40));
+                println!("ID: {}", equipement.id);
+                println!("{} ({})", equipement.nom, equipement.modele);
+                match equipement.statut {
+                    StatutEquipement::Fonctionnel => {
+                        println!("{}", equipement.statut.libelle().green())
+                    }
+                    StatutEquipement::EnMaintenance => {
+                        println!("{}", equipement.statut.libelle().yellow())
+                    }
+                    StatutEquipement::HorsService => {
+                        println!("{}", equipement.statut.libelle().red())
+                    }
+                }
+                println!("Dernière maintenance: {}", equipement.derniere_maintenance);
+                if !equipement.prochaine_maintenance.is_empty() {
+                    println!(
+                        "Prochaine maintenance: {} (tous les {} jours)",
+                        equipement.prochaine_maintenance, equipement.intervalle_maintenance
+                    );
+                }
+                if equipement.maintenance_en_retard(aujourdhui) {
+                    println!("{}", "⚠️ Maintenance en retard!".red());
+                }
+            }
+        }
+    }
+
+    fn enregistrer_intervention(&mut self) {
+        println!("{}", "\n=== INTERVENTION DE MAINTENANCE ===".green());
+        let (service_id, equipement_id) = match self.choisir_equipement() {
+            Some(choix) => choix,
+            None => return,
+        };
+        println!("1. Maintenance préventive");
+        println!("2. Maintenance corrective");
+        let type_intervention = match lire_nombre("Type: ") {
+            1 => TypeIntervention::Preventive,
+            2 => TypeIntervention::Corrective,
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+        let date = lire_chaine("Date de l'intervention (JJ/MM/AAAA): ");
+        let jour = match parse_date(&date) {
+            Some(jour) => jour,
+            None => {
+                println!("{}", "Date invalide!".red());
+                return;
+            }
+        };
+        let intervenant = lire_chaine("Intervenant: ");
+        let description = lire_chaine("Travaux réalisés: ");
+        let mut remise_en_service =
+            lire_chaine("Remettre l'équipement en service (o/n): ").eq_ignore_ascii_case("o");
+        if remise_en_service {
+            if let Some(ticket) = self.ticket_ouvert(service_id, equipement_id) {
+                println!(
+                    "{}",
+                    format!(
+                        "⚠️ Ticket N°{} ouvert: l'équipement ne sera remis en service qu'à sa clôture.",
+                        ticket
+                    )
+                    .yellow()
+                );
+                remise_en_service = false;
+            }
+        }
+
+        let equipement = match self.equipement_mut(service_id, equipement_id) {
+            Some(equipement) => equipement,
+            None => return,
+        };
+        // La date de référence de l'échéancier ne recule jamais
+        if parse_date(&equipement.derniere_maintenance).is_none_or(|d| d <= jour) {
+            equipement.derniere_maintenance = date.clone();
+            if type_intervention == TypeIntervention::Preventive {
+                equipement.planifier_prochaine(jour);
+            }
+        }
+        equipement.interventions.push(InterventionMaintenance {
+            date,
+            type_intervention,
+            intervenant,
+            description,
+            saisie_le: maintenant(),
+        });
+        if remise_en_service && equipement.statut != StatutEquipement::Fonctionnel {
+            equipement.changer_statut(
+                StatutEquipement::Fonctionnel,
+                "Remis en service après intervention",
+            );
+        }
+        self.save_data();
+        println!("{}", "\nIntervention enregistrée!".green());
+    }
+
+    fn historique_interventions(&self) {
+        let (service_id, equipement_id) = match self.choisir_equipement() {
+            Some(choix) => choix,
+            None => return,
+        };
+        let equipement = match self
+            .services
+            .iter()
+            .find(|s| s.id == service_id)
+            .and_then(|s| s.equipements.iter().find(|e| e.id == equipement_id))
+        {
+            Some(equipement) => equipement,
+            None => return,
+        };
+        println!(
+            "{}",
+            format!("\n=== JOURNAL DE MAINTENANCE: {} ===", equipement.nom).green()
+        );
+        for intervention in &equipement.interventions {
+            println!("{}", "-".repeat(40));
+            println!(
+                "{} - {:?} par {}",
+                intervention.date, intervention.type_intervention, intervention.intervenant
+            );
+            println!("{}", intervention.description);
+            println!("Saisie le: {}", intervention.saisie_le);
+        }
+    }
+
+    fn changer_statut_equipement(&mut self) {
+        println!("{}", "\n=== STATUT D'UN ÉQUIPEMENT ===".green());
+        let (service_id, equipement_id) = match self.choisir_equipement() {
+            Some(choix) => choix,
+            None => return,
+        };
+        println!("1. Fonctionnel");
+        println!("2. En maintenance");
+        println!("3. Hors service");
+        let nouveau = match lire_nombre("Nouveau statut: ") {
+            1 => StatutEquipement::Fonctionnel,
+            2 => StatutEquipement::EnMaintenance,
+            3 => StatutEquipement::HorsService,
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+        if nouveau == StatutEquipement::Fonctionnel {
+            if let Some(ticket) = self.ticket_ouvert(service_id, equipement_id) {
+                println!(
+                    "{}",
+                    format!(
+                        "Ticket N°{} ouvert: l'équipement sera remis en service à la clôture du ticket (Pannes et réparations)!",
+                        ticket
+                    )
+                    .red()
+                );
+                return;
+            }
+        }
+        let motif = lire_chaine("Motif: ");
+
+        let equipement = match self.equipement_mut(service_id, equipement_id) {
+            Some(equipement) => equipement,
+            None => return,
+        };
+        if equipement.statut == nouveau {
+            println!("{}", "Le statut est inchangé!".red());
+            return;
+        }
+        equipement.changer_statut(nouveau, &motif);
+        self.save_data();
+        println!("{}", "\nStatut mis à jour!".green());
+    }
+
+    fn afficher_echeances_maintenance(&self, horizon: i64) -> usize {
+        let aujourdhui = chrono::Local::now().date_naive();
+        let limite = aujourdhui + Duration::days(horizon);
+        let mut echeances: Vec<(&str, &Equipement, NaiveDate)> = self
+            .services
+            .iter()
+            .flat_map(|s| s.equipements.iter().map(move |e| (s.nom.as_str(), e)))
+            .filter(|(_, e)| e.statut != StatutEquipement::HorsService)
+            .filter_map(|(service, e)| Some((service, e, e.prochaine_maintenance()?)))
+            .filter(|(_, _, date)| *date <= limite)
+            .collect();
+        echeances.sort_by_key(|(_, _, date)| *date);
+
+        for (service, equipement, date) in &echeances {
+            let ligne = format!(
+                "{} - {} N°{} ({}): maintenance prévue le {}",
+                service,
+                equipement.nom,
+                equipement.id,
+                equipement.modele,
+                date.format(FORMAT_DATE)
+            );
+            if *date < aujourdhui {
+                println!("{}", format!("{} - en retard", ligne).red());
+            } else {
+                println!("{}", ligne.yellow());
+            }
+        }
+        echeances.len()
+    }
+
+    // Rappel affiché au démarrage
+    pub fn alertes_maintenance(&self) {
+        let aujourdhui = chrono::Local::now().date_naive();
+        let en_retard = self
+            .services
+            .iter()
+            .flat_map(|s| &s.equipements)
+            .filter(|e| e.maintenance_en_retard(aujourdhui))
+            .count();
+        if en_retard > 0 {
+            println!(
+                "{}",
+                format!("⚠️ {} équipement(s) en retard de maintenance:", en_retard).yellow()
+            );
+            self.afficher_echeances_maintenance(-1);
+        }
+    }
+
+    fn liste_echeances_maintenance(&self) {
+        println!("{}", "\n=== ÉCHÉANCIER DE MAINTENANCE ===".green());
+        if self.afficher_echeances_maintenance(HORIZON_MAINTENANCE_JOURS) == 0 {
+            println!(
+                "Aucune maintenance prévue dans les {} prochains jours.",
+                HORIZON_MAINTENANCE_JOURS
+            );
+        }
+    }
+
+    pub fn menu_equipements(&mut self) {
+        loop {
+            println!("\n{}", "=== ÉQUIPEMENTS ===".blue().bold());
+            println!("1. Enregistrer un équipement");
+            println!("2. Inventaire");
+            println!("3. Enregistrer une intervention de maintenance");
+            println!("4. Journal de maintenance d'un équipement");
+            println!("5. Changer le statut d'un équipement");
+            println!("6. Échéancier de maintenance");
+            println!("7. Réservations");
+            println!("8. Pannes et réparations");
+            println!("9. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.ajouter_equipement(),
+                2 => self.inventaire_equipements(),
+                3 => self.enregistrer_intervention(),
+                4 => self.historique_interventions(),
+                5 => self.changer_statut_equipement(),
+                6 => self.liste_echeances_maintenance(),
+                7 => self.menu_reservations_equipement(),
+                8 => self.menu_pannes(),
+                9 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
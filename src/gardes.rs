@@ -0,0 +1,430 @@
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use colored::*;
+
+use crate::planning::nom_jour;
+use crate::{lire_chaine, lire_nombre, parse_date, Application, Garde, FORMAT_DATE};
+
+struct Contraintes {
+    qualifications: Vec<String>,
+    max_consecutives: u32,
+    repos_jours: u32,
+}
+
+struct ProjetGardes {
+    service_id: u32,
+    affectations: Vec<(NaiveDate, Option<u32>)>,
+    problemes: Vec<(NaiveDate, String)>,
+}
+
+// Dimanche de Pâques (algorithme de Meeus/Jones/Butcher)
+fn paques(annee: i32) -> NaiveDate {
+    let a = annee % 19;
+    let b = annee / 100;
+    let c = annee % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let mois = (h + l - 7 * m + 114) / 31;
+    let jour = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(annee, mois as u32, jour as u32).unwrap()
+}
+
+pub fn est_ferie(date: NaiveDate) -> bool {
+    let fixes = [
+        (1, 1),
+        (5, 1),
+        (5, 8),
+        (7, 14),
+        (8, 15),
+        (11, 1),
+        (11, 11),
+        (12, 25),
+    ];
+    if fixes.contains(&(date.month(), date.day())) {
+        return true;
+    }
+    let dimanche_paques = paques(date.year());
+    [1, 39, 50]
+        .iter()
+        .any(|decalage| dimanche_paques + Duration::days(*decalage) == date)
+}
+
+// Week-end ou jour férié: ces gardes sont réparties à part pour l'équité
+pub fn est_jour_special(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun) || est_ferie(date)
+}
+
+// Vérifie qu'une garde supplémentaire à `date` respecte le nombre maximal
+// de gardes consécutives et le repos exigé entre deux séries de gardes.
+fn respecte_rythme(
+    gardes: &BTreeSet<NaiveDate>,
+    date: NaiveDate,
+    contraintes: &Contraintes,
+) -> bool {
+    if gardes.contains(&date) {
+        return false;
+    }
+    let mut debut = date;
+    while gardes.contains(&(debut - Duration::days(1))) {
+        debut -= Duration::days(1);
+    }
+    let mut fin = date;
+    while gardes.contains(&(fin + Duration::days(1))) {
+        fin += Duration::days(1);
+    }
+    if (fin - debut).num_days() + 1 > contraintes.max_consecutives as i64 {
+        return false;
+    }
+
+    // Un écart strictement supérieur à `repos` laisse `repos` jours libres entre deux séries
+    let repos = contraintes.repos_jours as i64;
+    let precedente = gardes.range(..debut).next_back();
+    let suivante = gardes.range(fin + Duration::days(1)..).next();
+    precedente.is_none_or(|p| (debut - *p).num_days() > repos)
+        && suivante.is_none_or(|s| (*s - fin).num_days() > repos)
+}
+
+impl Application {
+    fn dates_gardes(&self, personnel_id: u32) -> BTreeSet<NaiveDate> {
+        self.personnel
+            .iter()
+            .find(|p| p.id == personnel_id)
+            .map(|p| {
+                p.planning
+                    .gardes
+                    .iter()
+                    .filter_map(|g| parse_date(&g.date))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn generer_gardes(
+        &self,
+        service_id: u32,
+        annee: i32,
+        mois: u32,
+        contraintes: &Contraintes,
+    ) -> Option<ProjetGardes> {
+        let service = self.services.iter().find(|s| s.id == service_id)?;
+        let premier = NaiveDate::from_ymd_opt(annee, mois, 1)?;
+        let candidats = self.membres_du_service(service_id);
+
+        let mut gardes: Vec<(u32, BTreeSet<NaiveDate>)> = candidats
+            .iter()
+            .map(|id| (*id, self.dates_gardes(*id)))
+            .collect();
+        let mut projet = ProjetGardes {
+            service_id,
+            affectations: Vec::new(),
+            problemes: Vec::new(),
+        };
+
+        for date in premier.iter_days().take_while(|d| d.month() == mois) {
+            let date_texte = date.format(FORMAT_DATE).to_string();
+            let deja_couverte = self.personnel.iter().any(|p| {
+                p.planning
+                    .gardes
+                    .iter()
+                    .any(|g| g.service == service.nom && g.date == date_texte)
+            });
+            if deja_couverte {
+                continue;
+            }
+
+            let (mut indisponibles, mut conges, mut non_qualifies, mut repos) = (0, 0, 0, 0);
+            let mut eligibles: Vec<(u32, usize, usize)> = Vec::new();
+            for (id, dates) in &gardes {
+                let membre = match self.personnel.iter().find(|p| p.id == *id) {
+                    Some(membre) => membre,
+                    None => continue,
+                };
+                if !membre.disponible_le(date) {
+                    indisponibles += 1;
+                    continue;
+                }
+                if membre.planning.en_conge(date) {
+                    conges += 1;
+                    continue;
+                }
+                if membre
+                    .qualification_manquante(&contraintes.qualifications, date)
+                    .is_some()
+                {
+                    non_qualifies += 1;
+                    continue;
+                }
+                if !respecte_rythme(dates, date, contraintes) {
+                    repos += 1;
+                    continue;
+                }
+                let du_mois = dates
+                    .iter()
+                    .filter(|d| d.month() == mois && d.year() == annee);
+                let speciales = du_mois.clone().filter(|d| est_jour_special(**d)).count();
+                eligibles.push((*id, speciales, du_mois.count()));
+            }
+
+            // Les week-ends et fériés vont d'abord à ceux qui en ont eu le moins
+            let special = est_jour_special(date);
+            eligibles.sort_by_key(|(id, speciales, total)| {
+                if special {
+                    (*speciales, *total, *id)
+                } else {
+                    (*total, *speciales, *id)
+                }
+            });
+
+            match eligibles.first() {
+                Some((id, _, _)) => {
+                    if let Some((_, dates)) = gardes.iter_mut().find(|(p, _)| p == id) {
+                        dates.insert(date);
+                    }
+                    projet.affectations.push((date, Some(*id)));
+                }
+                None => {
+                    projet.affectations.push((date, None));
+                    projet.problemes.push((
+                        date,
+                        format!(
+                            "aucun candidat ({} en arrêt, suspendu(s) ou parti(s), {} en congé, {} non qualifié(s), {} sans repos suffisant, {} au total)",
+                            indisponibles,
+                            conges,
+                            non_qualifies,
+                            repos,
+                            candidats.len()
+                        ),
+                    ));
+                }
+            }
+        }
+        Some(projet)
+    }
+
+    // Membres rattachés à un service (affectation ou horaires); la disponibilité
+    // (arrêt, suspension, départ) se vérifie à la date de chaque garde
+    pub fn membres_du_service(&self, service_id: u32) -> Vec<u32> {
+        self.personnel
+            .iter()
+            .filter(|p| self.services_du_membre(p.id).contains(&service_id))
+            .map(|p| p.id)
+            .collect()
+    }
+
+    fn afficher_projet_gardes(&self, projet: &ProjetGardes) {
+        for (date, affectation) in &projet.affectations {
+            let nom = affectation
+                .and_then(|id| self.personnel.iter().find(|p| p.id == id))
+                .map(|p| format!("{} {}", p.nom, p.prenom));
+            let marque = if est_jour_special(*date) {
+                " (WE/férié)"
+            } else {
+                ""
+            };
+            let ligne = format!(
+                "{} {}{}: {}",
+                nom_jour(date.weekday()),
+                date.format(FORMAT_DATE),
+                marque,
+                nom.clone().unwrap_or_else(|| "NON POURVUE".to_string())
+            );
+            if nom.is_some() {
+                println!("{}", ligne);
+            } else {
+                println!("{}", ligne.red());
+            }
+        }
+
+        println!("\n--- Répartition ---");
+        for id in self.membres_du_service(projet.service_id) {
+            // Les membres indisponibles sur tout le mois ne figurent pas dans la répartition
+            let disponible = self
+                .personnel
+                .iter()
+                .find(|p| p.id == id)
+                .is_some_and(|p| projet.affectations.iter().any(|(d, _)| p.disponible_le(*d)));
+            if !disponible {
+                continue;
+            }
+            let attribuees: Vec<&NaiveDate> = projet
+                .affectations
+                .iter()
+                .filter(|(_, a)| *a == Some(id))
+                .map(|(d, _)| d)
+                .collect();
+            if let Some(membre) = self.personnel.iter().find(|p| p.id == id) {
+                println!(
+                    "{} {}: {} garde(s) dont {} week-end/férié",
+                    membre.nom,
+                    membre.prenom,
+                    attribuees.len(),
+                    attribuees.iter().filter(|d| est_jour_special(***d)).count()
+                );
+            }
+        }
+
+        if !projet.problemes.is_empty() {
+            println!("\n{}", "--- Contraintes non satisfaites ---".red());
+            for (date, raison) in &projet.problemes {
+                println!("{}: {}", date.format(FORMAT_DATE), raison);
+            }
+        }
+    }
+
+    pub fn planifier_gardes(&mut self) {
+        println!("{}", "\n=== GÉNÉRATION DU TABLEAU DE GARDES ===".green());
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        let periode = lire_chaine("Mois (MM/AAAA): ");
+        let (mois, annee) = match periode.split_once('/') {
+            Some((m, a)) => match (m.parse::<u32>(), a.parse::<i32>()) {
+                (Ok(m), Ok(a)) => (m, a),
+                _ => {
+                    println!("{}", "Mois invalide!".red());
+                    return;
+                }
+            },
+            None => {
+                println!("{}", "Mois invalide!".red());
+                return;
+            }
+        };
+
+        let mut qualifications = match self.services.iter().find(|s| s.id == service_id) {
+            Some(service) => service.qualifications_garde.clone(),
+            None => Vec::new(),
+        };
+        if !qualifications.is_empty() {
+            println!(
+                "Qualifications exigées par le service: {}",
+                qualifications.join(", ")
+            );
+        }
+        let supplementaire = lire_chaine("Qualification supplémentaire (Entrée si aucune): ");
+        if !supplementaire.is_empty() {
+            qualifications.push(supplementaire);
+        }
+        let contraintes = Contraintes {
+            qualifications,
+            max_consecutives: lire_nombre("Nombre maximal de gardes consécutives: ").max(1),
+            repos_jours: lire_nombre("Jours de repos minimum après une garde: "),
+        };
+
+        let mut projet = match self.generer_gardes(service_id, annee, mois, &contraintes) {
+            Some(projet) => projet,
+            None => {
+                println!("{}", "Service ou mois invalide!".red());
+                return;
+            }
+        };
+        if projet.affectations.is_empty() {
+            println!(
+                "{}",
+                "Toutes les gardes du mois sont déjà attribuées.".yellow()
+            );
+            return;
+        }
+
+        loop {
+            self.afficher_projet_gardes(&projet);
+            println!("\n1. Modifier une affectation");
+            println!("2. Publier le tableau");
+            println!("3. Abandonner");
+            match lire_nombre("Choix: ") {
+                1 => {
+                    let date = match parse_date(&lire_chaine("Date (JJ/MM/AAAA): ")) {
+                        Some(date) => date,
+                        None => {
+                            println!("{}", "Date invalide!".red());
+                            continue;
+                        }
+                    };
+                    let personnel_id = lire_nombre("ID du membre du personnel: ");
+                    let membre = match self.personnel.iter().find(|p| p.id == personnel_id) {
+                        Some(membre) => membre,
+                        None => {
+                            println!("{}", "Membre du personnel non trouvé!".red());
+                            continue;
+                        }
+                    };
+                    if !self.membres_du_service(service_id).contains(&personnel_id) {
+                        println!("{}", "Ce membre n'est pas rattaché à ce service!".red());
+                        continue;
+                    }
+                    if !membre.disponible_le(date) {
+                        println!(
+                            "{}",
+                            "Ce membre est parti, en arrêt ou suspendu à cette date!".red()
+                        );
+                        continue;
+                    }
+                    if membre.planning.en_conge(date) {
+                        println!("{}", "Ce membre est en congé à cette date!".red());
+                        continue;
+                    }
+                    if let Some(manquante) =
+                        membre.qualification_manquante(&contraintes.qualifications, date)
+                    {
+                        println!(
+                            "{}",
+                            format!("Qualification {} manquante à cette date!", manquante).red()
+                        );
+                        continue;
+                    }
+                    match projet.affectations.iter_mut().find(|(d, _)| *d == date) {
+                        Some(affectation) => {
+                            affectation.1 = Some(personnel_id);
+                            projet.problemes.retain(|(d, _)| *d != date);
+                            let mut dates = self.dates_gardes(personnel_id);
+                            for (d, a) in &projet.affectations {
+                                if *a == Some(personnel_id) && *d != date {
+                                    dates.insert(*d);
+                                }
+                            }
+                            if !respecte_rythme(&dates, date, &contraintes) {
+                                println!(
+                                    "{}",
+                                    "⚠️ Affectation forcée: règles de repos non respectées"
+                                        .yellow()
+                                );
+                            }
+                        }
+                        None => println!("{}", "Date hors du tableau!".red()),
+                    }
+                }
+                2 => break,
+                3 => return,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+
+        let nom_service = match self.services.iter().find(|s| s.id == service_id) {
+            Some(service) => service.nom.clone(),
+            None => return,
+        };
+        let mut publiees = 0;
+        for (date, affectation) in &projet.affectations {
+            let id = match affectation {
+                Some(id) => *id,
+                None => continue,
+            };
+            if let Some(membre) = self.personnel.iter_mut().find(|p| p.id == id) {
+                membre.planning.gardes.push(Garde {
+                    date: date.format(FORMAT_DATE).to_string(),
+                    service: nom_service.clone(),
+                });
+                publiees += 1;
+            }
+        }
+        self.save_data();
+        println!("{}", format!("\n{} garde(s) publiée(s)!", publiees).green());
+    }
+}
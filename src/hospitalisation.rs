@@ -0,0 +1,503 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{lire_chaine, lire_nombre, maintenant, parse_date_heure, Application};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Sortie {
+    date: String,
+    medecin_id: u32,
+    destination: String,
+    resume: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Transfert {
+    date: String,
+    service_origine: u32,
+    lit_origine: u32,
+    service_destination: u32,
+    lit_destination: u32,
+    motif: String,
+    medecin_id: u32,
+}
+
+// Portion d'un séjour passée dans un même service
+struct Etape {
+    service_id: u32,
+    debut: String,
+    fin: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Sejour {
+    id: u32,
+    patient_id: u32,
+    service_id: u32,
+    lit: u32,
+    motif: String,
+    admission: String,
+    sortie: Option<Sortie>,
+    #[serde(default)]
+    transferts: Vec<Transfert>,
+}
+
+fn jours_entre(debut: &str, fin: Option<&str>) -> i64 {
+    let debut = match parse_date_heure(debut) {
+        Some(d) => d,
+        None => return 0,
+    };
+    let fin = fin
+        .and_then(parse_date_heure)
+        .unwrap_or_else(|| chrono::Local::now().naive_local());
+    (fin.date() - debut.date()).num_days().max(0)
+}
+
+impl Sejour {
+    fn en_cours(&self) -> bool {
+        self.sortie.is_none()
+    }
+
+    // Durée du séjour en jours, jusqu'à la sortie ou jusqu'à aujourd'hui
+    fn duree(&self) -> i64 {
+        jours_entre(
+            &self.admission,
+            self.sortie.as_ref().map(|s| s.date.as_str()),
+        )
+    }
+
+    // Services successivement fréquentés, reconstitués à partir des transferts
+    fn itineraire(&self) -> Vec<Etape> {
+        let mut etapes = Vec::new();
+        let mut service_id = self
+            .transferts
+            .first()
+            .map(|t| t.service_origine)
+            .unwrap_or(self.service_id);
+        let mut debut = self.admission.clone();
+
+        for transfert in &self.transferts {
+            etapes.push(Etape {
+                service_id,
+                debut,
+                fin: Some(transfert.date.clone()),
+            });
+            service_id = transfert.service_destination;
+            debut = transfert.date.clone();
+        }
+        etapes.push(Etape {
+            service_id,
+            debut,
+            fin: self.sortie.as_ref().map(|s| s.date.clone()),
+        });
+        etapes
+    }
+
+    // Nombre de journées attribuées à chaque service pour la facturation
+    fn jours_par_service(&self) -> Vec<(u32, i64)> {
+        let mut jours: Vec<(u32, i64)> = Vec::new();
+        for etape in self.itineraire() {
+            let duree = jours_entre(&etape.debut, etape.fin.as_deref());
+            match jours.iter_mut().find(|(id, _)| *id == etape.service_id) {
+                Some((_, total)) => *total += duree,
+                None => jours.push((etape.service_id, duree)),
+            }
+        }
+        jours
+    }
+}
+
+impl Application {
+    pub fn lits_occupes(&self, service_id: u32) -> Vec<u32> {
+        self.sejours
+            .iter()
+            .filter(|s| s.en_cours() && s.service_id == service_id)
+            .map(|s| s.lit)
+            .collect()
+    }
+
+    fn lits_libres(&self, service_id: u32) -> Vec<u32> {
+        let capacite = match self.services.iter().find(|s| s.id == service_id) {
+            Some(service) => service.capacite,
+            None => return Vec::new(),
+        };
+        let occupes = self.lits_occupes(service_id);
+        (1..=capacite)
+            .filter(|lit| !occupes.contains(lit))
+            .collect()
+    }
+
+    // Demande un lit libre du service, en proposant le premier par défaut
+    fn choisir_lit(&self, service_id: u32) -> Option<u32> {
+        let libres = self.lits_libres(service_id);
+        if libres.is_empty() {
+            println!("{}", "Service complet, aucun lit disponible!".red());
+            return None;
+        }
+        println!("Lits disponibles: {:?}", libres);
+        let saisie = lire_chaine(&format!(
+            "Numéro de lit (Entrée pour le lit {}): ",
+            libres[0]
+        ));
+        if saisie.is_empty() {
+            return Some(libres[0]);
+        }
+        match saisie.parse::<u32>() {
+            Ok(lit) if libres.contains(&lit) => Some(lit),
+            _ => {
+                println!("{}", "Lit invalide ou occupé!".red());
+                None
+            }
+        }
+    }
+
+    pub fn admettre_patient(&mut self) {
+        println!("{}", "\n=== ADMISSION D'UN PATIENT ===".green());
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+
+        if !self.patients.iter().any(|p| p.id == patient_id) {
+            println!("{}", "Patient non trouvé!".red());
+            return;
+        }
+        if self
+            .sejours
+            .iter()
+            .any(|s| s.en_cours() && s.patient_id == patient_id)
+        {
+            println!("{}", "Ce patient est déjà hospitalisé!".red());
+            return;
+        }
+
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        if !self
+            .services
+            .iter()
+            .any(|s| s.id == service_id && s.actif())
+        {
+            println!("{}", "Service non trouvé ou désactivé!".red());
+            return;
+        }
+
+        let lit = match self.choisir_lit(service_id) {
+            Some(lit) => lit,
+            None => return,
+        };
+        let motif = lire_chaine("Motif d'hospitalisation: ");
+
+        let sejour = Sejour {
+            id: (self.sejours.len() + 1) as u32,
+            patient_id,
+            service_id,
+            lit,
+            motif,
+            admission: maintenant(),
+            sortie: None,
+            transferts: Vec::new(),
+        };
+
+        self.sejours.push(sejour);
+        self.save_data();
+        println!("{}", "\nPatient admis avec succès!".green());
+    }
+
+    pub fn changer_lit(&mut self) {
+        println!("{}", "\n=== CHANGEMENT DE LIT ===".green());
+        self.liste_hospitalises();
+        let sejour_id = lire_nombre("ID du séjour: ");
+
+        let service_id = match self
+            .sejours
+            .iter()
+            .find(|s| s.id == sejour_id && s.en_cours())
+        {
+            Some(sejour) => sejour.service_id,
+            None => {
+                println!("{}", "Séjour en cours non trouvé!".red());
+                return;
+            }
+        };
+
+        let lit = match self.choisir_lit(service_id) {
+            Some(lit) => lit,
+            None => return,
+        };
+
+        if let Some(sejour) = self.sejours.iter_mut().find(|s| s.id == sejour_id) {
+            sejour.lit = lit;
+            self.save_data();
+            println!("{}", "\nPatient déplacé avec succès!".green());
+        }
+    }
+
+    pub fn transferer_patient(&mut self) {
+        println!("{}", "\n=== TRANSFERT INTER-SERVICES ===".green());
+        self.liste_hospitalises();
+        let sejour_id = lire_nombre("ID du séjour: ");
+
+        let (service_origine, lit_origine) = match self
+            .sejours
+            .iter()
+            .find(|s| s.id == sejour_id && s.en_cours())
+        {
+            Some(sejour) => (sejour.service_id, sejour.lit),
+            None => {
+                println!("{}", "Séjour en cours non trouvé!".red());
+                return;
+            }
+        };
+
+        self.liste_services();
+        let service_destination = lire_nombre("ID du service de destination: ");
+        if service_destination == service_origine {
+            println!("{}", "Le patient est déjà dans ce service!".red());
+            return;
+        }
+        if !self
+            .services
+            .iter()
+            .any(|s| s.id == service_destination && s.actif())
+        {
+            println!("{}", "Service non trouvé ou désactivé!".red());
+            return;
+        }
+
+        // Le lit de destination est réservé avant toute modification du séjour
+        let lit_destination = match self.choisir_lit(service_destination) {
+            Some(lit) => lit,
+            None => return,
+        };
+
+        let medecin_id = lire_nombre("ID du médecin prescripteur: ");
+        if !self.personnel.iter().any(|p| p.id == medecin_id) {
+            println!("{}", "Médecin non trouvé!".red());
+            return;
+        }
+        let motif = lire_chaine("Motif du transfert: ");
+
+        if let Some(sejour) = self.sejours.iter_mut().find(|s| s.id == sejour_id) {
+            sejour.transferts.push(Transfert {
+                date: maintenant(),
+                service_origine,
+                lit_origine,
+                service_destination,
+                lit_destination,
+                motif,
+                medecin_id,
+            });
+            sejour.service_id = service_destination;
+            sejour.lit = lit_destination;
+            self.save_data();
+            println!("{}", "\nPatient transféré avec succès!".green());
+        }
+    }
+
+    pub fn sortie_patient(&mut self) {
+        println!("{}", "\n=== SORTIE D'UN PATIENT ===".green());
+        self.liste_hospitalises();
+        let sejour_id = lire_nombre("ID du séjour: ");
+
+        if !self
+            .sejours
+            .iter()
+            .any(|s| s.id == sejour_id && s.en_cours())
+        {
+            println!("{}", "Séjour en cours non trouvé!".red());
+            return;
+        }
+
+        let medecin_id = lire_nombre("ID du médecin signataire: ");
+        if !self.personnel.iter().any(|p| p.id == medecin_id) {
+            println!("{}", "Médecin non trouvé!".red());
+            return;
+        }
+        let destination = lire_chaine("Destination (domicile, autre établissement...): ");
+        let resume = lire_chaine("Résumé de sortie: ");
+
+        if let Some(sejour) = self.sejours.iter_mut().find(|s| s.id == sejour_id) {
+            sejour.sortie = Some(Sortie {
+                date: maintenant(),
+                medecin_id,
+                destination,
+                resume,
+            });
+            println!("Durée du séjour: {} jour(s)", sejour.duree());
+            self.save_data();
+            println!("{}", "\nSortie enregistrée avec succès!".green());
+        }
+    }
+
+    pub fn liste_hospitalises(&self) {
+        println!("{}", "\n=== PATIENTS HOSPITALISÉS ===".green());
+        for sejour in self.sejours.iter().filter(|s| s.en_cours()) {
+            println!("{}", "-".repeat(40));
+            println!("Séjour N°{}", sejour.id);
+            if let Some(patient) = self.patients.iter().find(|p| p.id == sejour.patient_id) {
+                println!("Patient: {} {}", patient.nom, patient.prenom);
+            }
+            if let Some(service) = self.services.iter().find(|s| s.id == sejour.service_id) {
+                println!("Service: {} - Lit {}", service.nom, sejour.lit);
+            }
+            println!("Motif: {}", sejour.motif);
+            println!("Admission: {}", sejour.admission);
+            println!("Durée: {} jour(s)", sejour.duree());
+        }
+    }
+
+    pub fn historique_sejours(&self) {
+        println!("{}", "\n=== HISTORIQUE DES SÉJOURS ===".green());
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+
+        for sejour in self.sejours.iter().filter(|s| s.patient_id == patient_id) {
+            println!("{}", "-".repeat(40));
+            println!("Séjour N°{} - {}", sejour.id, sejour.motif);
+            println!("Admission: {}", sejour.admission);
+            println!("Durée: {} jour(s)", sejour.duree());
+
+            println!("Itinéraire:");
+            for etape in sejour.itineraire() {
+                let nom = self
+                    .services
+                    .iter()
+                    .find(|s| s.id == etape.service_id)
+                    .map(|s| s.nom.as_str())
+                    .unwrap_or("Service inconnu");
+                println!(
+                    "  {} : du {} au {} ({} jour(s))",
+                    nom,
+                    etape.debut,
+                    etape.fin.as_deref().unwrap_or("..."),
+                    jours_entre(&etape.debut, etape.fin.as_deref())
+                );
+            }
+            for transfert in &sejour.transferts {
+                print!(
+                    "  Transfert du {} (lit {} -> lit {}): {}",
+                    transfert.date,
+                    transfert.lit_origine,
+                    transfert.lit_destination,
+                    transfert.motif
+                );
+                match self.personnel.iter().find(|p| p.id == transfert.medecin_id) {
+                    Some(medecin) => {
+                        println!(", prescrit par Dr. {} {}", medecin.nom, medecin.prenom)
+                    }
+                    None => println!(),
+                }
+            }
+            match &sejour.sortie {
+                Some(sortie) => {
+                    println!("Sortie: {} vers {}", sortie.date, sortie.destination);
+                    if let Some(medecin) = self.personnel.iter().find(|p| p.id == sortie.medecin_id)
+                    {
+                        println!("Signée par: Dr. {} {}", medecin.nom, medecin.prenom);
+                    }
+                    println!("Résumé: {}", sortie.resume);
+                }
+                None => println!("{}", "En cours".yellow()),
+            }
+        }
+    }
+
+    pub fn occupation_services(&self) {
+        println!("{}", "\n=== OCCUPATION DES SERVICES ===".green());
+        for service in &self.services {
+            let occupes = self.lits_occupes(service.id);
+            println!("{}", "-".repeat(40));
+            println!("Service: {}", service.nom);
+            let taux = if service.capacite > 0 {
+                occupes.len() as f64 * 100.0 / service.capacite as f64
+            } else {
+                0.0
+            };
+            let ligne = format!(
+                "Lits occupés: {}/{} ({:.0}%)",
+                occupes.len(),
+                service.capacite,
+                taux
+            );
+            if occupes.len() as u32 >= service.capacite {
+                println!("{}", ligne.red());
+            } else {
+                println!("{}", ligne);
+            }
+            for sejour in self
+                .sejours
+                .iter()
+                .filter(|s| s.en_cours() && s.service_id == service.id)
+            {
+                if let Some(patient) = self.patients.iter().find(|p| p.id == sejour.patient_id) {
+                    println!(
+                        "  Lit {}: {} {} ({} jour(s))",
+                        sejour.lit,
+                        patient.nom,
+                        patient.prenom,
+                        sejour.duree()
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn statistiques_hospitalisation(&self) {
+        println!("\n--- Hospitalisation ---");
+        let en_cours = self.sejours.iter().filter(|s| s.en_cours()).count();
+        let capacite: u32 = self.services.iter().map(|s| s.capacite).sum();
+        println!("Patients hospitalisés: {}/{}", en_cours, capacite);
+
+        let termines: Vec<i64> = self
+            .sejours
+            .iter()
+            .filter(|s| !s.en_cours())
+            .map(|s| s.duree())
+            .collect();
+        if !termines.is_empty() {
+            let moyenne = termines.iter().sum::<i64>() as f64 / termines.len() as f64;
+            println!("Durée moyenne de séjour: {:.1} jour(s)", moyenne);
+        }
+
+        for service in &self.services {
+            let journees: i64 = self
+                .sejours
+                .iter()
+                .flat_map(|s| s.jours_par_service())
+                .filter(|(id, _)| *id == service.id)
+                .map(|(_, jours)| jours)
+                .sum();
+            if journees > 0 {
+                println!(
+                    "  {}: {} journée(s) d'hospitalisation",
+                    service.nom, journees
+                );
+            }
+        }
+    }
+
+    pub fn menu_hospitalisation(&mut self) {
+        loop {
+            println!("\n{}", "=== HOSPITALISATION ===".blue().bold());
+            println!("1. Admettre un patient");
+            println!("2. Changer de lit");
+            println!("3. Transférer vers un autre service");
+            println!("4. Sortie d'un patient");
+            println!("5. Patients hospitalisés");
+            println!("6. Occupation des services");
+            println!("7. Historique des séjours d'un patient");
+            println!("8. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.admettre_patient(),
+                2 => self.changer_lit(),
+                3 => self.transferer_patient(),
+                4 => self.sortie_patient(),
+                5 => self.liste_hospitalises(),
+                6 => self.occupation_services(),
+                7 => self.historique_sejours(),
+                8 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
@@ -0,0 +1,619 @@
+use std::fs;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime, TimeZone};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::conformite::vacations;
+use crate::{
+    lire_chaine, lire_nombre, parse_date_heure, Application, Personnel, StatutRendezVous,
+    FORMAT_DATE_HEURE,
+};
+
+// Suffixe des UID générés par l'application: un événement réimporté le porte toujours
+const DOMAINE_UID: &str = "@gestion-hospitaliere";
+const FORMAT_ICS: &str = "%Y%m%dT%H%M%S";
+const LARGEUR_LIGNE: usize = 75;
+// Nombre de jours de vacations exportés à partir d'aujourd'hui
+const HORIZON_VACATIONS_JOURS: i64 = 28;
+
+// Période pendant laquelle un membre du personnel ne peut pas recevoir de rendez-vous
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Indisponibilite {
+    id: u32,
+    personnel_id: u32,
+    uid: String,
+    debut: String,
+    fin: String,
+    motif: String,
+}
+
+impl Indisponibilite {
+    pub fn chevauche(&self, personnel_id: u32, debut: NaiveDateTime, fin: NaiveDateTime) -> bool {
+        if self.personnel_id != personnel_id {
+            return false;
+        }
+        match (parse_date_heure(&self.debut), parse_date_heure(&self.fin)) {
+            (Some(d), Some(f)) => d < fin && debut < f,
+            _ => false,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        format!("du {} au {} ({})", self.debut, self.fin, self.motif)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evenement {
+    pub uid: String,
+    pub debut: NaiveDateTime,
+    pub fin: NaiveDateTime,
+    pub resume: String,
+    pub description: String,
+    pub annule: bool,
+    pub sequence: u32,
+}
+
+fn echapper(texte: &str) -> String {
+    texte
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn desechapper(texte: &str) -> String {
+    let mut resultat = String::new();
+    let mut caracteres = texte.chars();
+    while let Some(c) = caracteres.next() {
+        if c != '\\' {
+            resultat.push(c);
+            continue;
+        }
+        match caracteres.next() {
+            Some('n') | Some('N') => resultat.push('\n'),
+            Some(autre) => resultat.push(autre),
+            None => {}
+        }
+    }
+    resultat
+}
+
+// Repli des lignes à 75 octets (RFC 5545, section 3.1)
+fn plier(ligne: &str) -> String {
+    let mut resultat = String::new();
+    let mut longueur = 0;
+    for c in ligne.chars() {
+        let taille = c.len_utf8();
+        if longueur + taille > LARGEUR_LIGNE {
+            resultat.push_str("\r\n ");
+            longueur = 1;
+        }
+        resultat.push(c);
+        longueur += taille;
+    }
+    resultat.push_str("\r\n");
+    resultat
+}
+
+pub fn exporter_ics(evenements: &[Evenement]) -> String {
+    let horodatage = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut ics = String::new();
+    ics.push_str(&plier("BEGIN:VCALENDAR"));
+    ics.push_str(&plier("VERSION:2.0"));
+    ics.push_str(&plier("PRODID:-//Gestion Hospitaliere//Rendez-vous//FR"));
+    ics.push_str(&plier("CALSCALE:GREGORIAN"));
+    for evenement in evenements {
+        ics.push_str(&plier("BEGIN:VEVENT"));
+        ics.push_str(&plier(&format!("UID:{}", evenement.uid)));
+        ics.push_str(&plier(&format!("DTSTAMP:{}", horodatage)));
+        ics.push_str(&plier(&format!("SEQUENCE:{}", evenement.sequence)));
+        ics.push_str(&plier(&format!(
+            "DTSTART:{}",
+            evenement.debut.format(FORMAT_ICS)
+        )));
+        ics.push_str(&plier(&format!(
+            "DTEND:{}",
+            evenement.fin.format(FORMAT_ICS)
+        )));
+        ics.push_str(&plier(&format!("SUMMARY:{}", echapper(&evenement.resume))));
+        if !evenement.description.is_empty() {
+            ics.push_str(&plier(&format!(
+                "DESCRIPTION:{}",
+                echapper(&evenement.description)
+            )));
+        }
+        let statut = if evenement.annule {
+            "CANCELLED"
+        } else {
+            "CONFIRMED"
+        };
+        ics.push_str(&plier(&format!("STATUS:{}", statut)));
+        ics.push_str(&plier("END:VEVENT"));
+    }
+    ics.push_str(&plier("END:VCALENDAR"));
+    ics
+}
+
+// Valeur d'une date iCalendar: locale, UTC (suffixe Z) ou journée entière
+fn lire_date_ics(parametres: &str, valeur: &str) -> Option<NaiveDateTime> {
+    if parametres.contains("VALUE=DATE") && !parametres.contains("VALUE=DATE-TIME") {
+        return NaiveDate::parse_from_str(valeur, "%Y%m%d")
+            .ok()
+            .and_then(|d| d.and_hms_opt(0, 0, 0));
+    }
+    match valeur.strip_suffix('Z') {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, FORMAT_ICS)
+            .ok()
+            .map(|d| chrono::Local.from_utc_datetime(&d).naive_local()),
+        None => NaiveDateTime::parse_from_str(valeur, FORMAT_ICS).ok(),
+    }
+}
+
+pub fn lire_ics(contenu: &str) -> Vec<Evenement> {
+    // Dépliage des lignes de continuation
+    let mut lignes: Vec<String> = Vec::new();
+    for ligne in contenu.lines() {
+        let ligne = ligne.trim_end_matches('\r');
+        match (
+            ligne.strip_prefix(' ').or_else(|| ligne.strip_prefix('\t')),
+            lignes.last_mut(),
+        ) {
+            (Some(suite), Some(derniere)) => derniere.push_str(suite),
+            _ => lignes.push(ligne.to_string()),
+        }
+    }
+
+    let mut evenements = Vec::new();
+    let mut courant: Option<Vec<(String, String, String)>> = None;
+    for ligne in lignes {
+        if ligne == "BEGIN:VEVENT" {
+            courant = Some(Vec::new());
+            continue;
+        }
+        if ligne == "END:VEVENT" {
+            if let Some(proprietes) = courant.take() {
+                if let Some(evenement) = construire_evenement(&proprietes) {
+                    evenements.push(evenement);
+                }
+            }
+            continue;
+        }
+        if let (Some(proprietes), Some((cle, valeur))) = (courant.as_mut(), ligne.split_once(':')) {
+            let (nom, parametres) = cle.split_once(';').unwrap_or((cle, ""));
+            proprietes.push((
+                nom.to_uppercase(),
+                parametres.to_uppercase(),
+                valeur.to_string(),
+            ));
+        }
+    }
+    evenements
+}
+
+fn construire_evenement(proprietes: &[(String, String, String)]) -> Option<Evenement> {
+    let propriete = |nom: &str| proprietes.iter().find(|(n, _, _)| n == nom);
+
+    let (_, parametres, valeur) = propriete("DTSTART")?;
+    let debut = lire_date_ics(parametres, valeur)?;
+    let journee = parametres.contains("VALUE=DATE") && !parametres.contains("VALUE=DATE-TIME");
+    let fin = match propriete("DTEND") {
+        Some((_, parametres, valeur)) => lire_date_ics(parametres, valeur)?,
+        None if journee => debut + Duration::days(1),
+        None => debut,
+    };
+
+    Some(Evenement {
+        uid: propriete("UID")
+            .map(|(_, _, v)| v.clone())
+            .unwrap_or_default(),
+        debut,
+        fin,
+        resume: propriete("SUMMARY")
+            .map(|(_, _, v)| desechapper(v))
+            .unwrap_or_default(),
+        description: propriete("DESCRIPTION")
+            .map(|(_, _, v)| desechapper(v))
+            .unwrap_or_default(),
+        annule: propriete("STATUS").is_some_and(|(_, _, v)| v == "CANCELLED")
+            || propriete("TRANSP").is_some_and(|(_, _, v)| v == "TRANSPARENT"),
+        sequence: propriete("SEQUENCE")
+            .and_then(|(_, _, v)| v.parse().ok())
+            .unwrap_or(0),
+    })
+}
+
+impl Application {
+    // Événements des rendez-vous correspondant au filtre, avec un UID stable par rendez-vous
+    fn evenements_rendez_vous<F>(&self, filtre: F) -> Vec<Evenement>
+    where
+        F: Fn(u32, u32) -> bool,
+    {
+        self.rendez_vous
+            .iter()
+            .filter(|r| filtre(r.patient_id, r.personnel_id))
+            .filter_map(|rdv| {
+                let patient = self.patients.iter().find(|p| p.id == rdv.patient_id)?;
+                let medecin = self.personnel.iter().find(|p| p.id == rdv.personnel_id)?;
+                Some(Evenement {
+                    uid: format!("rdv-{}{}", rdv.id, DOMAINE_UID),
+                    debut: rdv.debut()?,
+                    fin: rdv.fin()?,
+                    resume: format!(
+                        "Consultation {} {} - Dr. {} {}",
+                        patient.nom, patient.prenom, medecin.nom, medecin.prenom
+                    ),
+                    description: format!("Rendez-vous N°{} ({:?})", rdv.id, rdv.statut),
+                    annule: matches!(
+                        rdv.statut,
+                        StatutRendezVous::Annule | StatutRendezVous::Absent
+                    ),
+                    sequence: rdv.historique.len() as u32,
+                })
+            })
+            .collect()
+    }
+
+    // Vacations (horaires hors congés) et gardes d'un membre, avec un UID stable par vacation
+    fn evenements_vacations(
+        &self,
+        membre: &Personnel,
+        du: NaiveDate,
+        au: NaiveDate,
+    ) -> Vec<Evenement> {
+        vacations(membre, du, au)
+            .into_iter()
+            .map(|vacation| {
+                let (prefixe, resume) = if vacation.garde {
+                    ("garde", "Garde de nuit")
+                } else {
+                    ("vacation", "Vacation")
+                };
+                Evenement {
+                    uid: format!(
+                        "{}-{}-{}{}",
+                        prefixe,
+                        membre.id,
+                        vacation.debut.format("%Y%m%dT%H%M"),
+                        DOMAINE_UID
+                    ),
+                    debut: vacation.debut,
+                    fin: vacation.fin,
+                    resume: format!("{} - Dr. {} {}", resume, membre.nom, membre.prenom),
+                    description: String::new(),
+                    annule: false,
+                    sequence: 0,
+                }
+            })
+            .collect()
+    }
+
+    pub fn exporter_calendrier(&self) {
+        println!("{}", "\n=== EXPORT iCALENDAR ===".green());
+        println!("1. Par membre du personnel");
+        println!("2. Par service");
+        println!("3. Par patient");
+        println!("4. Vacations et gardes d'un membre du personnel");
+
+        let (evenements, nom_fichier) = match lire_nombre("Choix: ") {
+            1 => {
+                self.liste_personnel();
+                let id = lire_nombre("ID du membre du personnel: ");
+                (
+                    self.evenements_rendez_vous(|_, personnel_id| personnel_id == id),
+                    format!("personnel_{}.ics", id),
+                )
+            }
+            2 => {
+                self.liste_services();
+                let id = lire_nombre("ID du service: ");
+                let membres: Vec<u32> = match self.services.iter().find(|s| s.id == id) {
+                    Some(service) => {
+                        let mut membres = service.personnel_affecte.clone();
+                        membres.push(service.chef_service);
+                        membres
+                    }
+                    None => {
+                        println!("{}", "Service non trouvé!".red());
+                        return;
+                    }
+                };
+                (
+                    self.evenements_rendez_vous(|_, personnel_id| membres.contains(&personnel_id)),
+                    format!("service_{}.ics", id),
+                )
+            }
+            3 => {
+                self.liste_patients();
+                let id = lire_nombre("ID du patient: ");
+                (
+                    self.evenements_rendez_vous(|patient_id, _| patient_id == id),
+                    format!("patient_{}.ics", id),
+                )
+            }
+            4 => {
+                self.liste_personnel();
+                let id = lire_nombre("ID du membre du personnel: ");
+                let Some(membre) = self.personnel.iter().find(|p| p.id == id) else {
+                    println!("{}", "Membre du personnel non trouvé!".red());
+                    return;
+                };
+                let aujourd_hui = chrono::Local::now().date_naive();
+                (
+                    self.evenements_vacations(
+                        membre,
+                        aujourd_hui,
+                        aujourd_hui + Duration::days(HORIZON_VACATIONS_JOURS),
+                    ),
+                    format!("vacations_{}.ics", id),
+                )
+            }
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        let saisie = lire_chaine(&format!(
+            "Fichier de sortie (Entrée pour {}): ",
+            nom_fichier
+        ));
+        let chemin = if saisie.is_empty() {
+            nom_fichier
+        } else {
+            saisie
+        };
+        match fs::write(&chemin, exporter_ics(&evenements)) {
+            Ok(()) => println!(
+                "{}",
+                format!(
+                    "\n{} événement(s) exporté(s) vers {}",
+                    evenements.len(),
+                    chemin
+                )
+                .green()
+            ),
+            Err(erreur) => println!("{}", format!("Erreur d'écriture: {}", erreur).red()),
+        }
+    }
+
+    // Un UID déjà importé met à jour la période au lieu de la dupliquer, ou la
+    // supprime si l'organisateur a annulé l'événement.
+    // Renvoie le nombre de périodes ajoutées, mises à jour, supprimées et ignorées.
+    fn importer_evenements(
+        &mut self,
+        personnel_id: u32,
+        evenements: Vec<Evenement>,
+    ) -> (u32, u32, u32, u32) {
+        let (mut ajoutees, mut mises_a_jour, mut supprimees, mut ignorees) = (0, 0, 0, 0);
+        for evenement in evenements {
+            // Nos propres exports ne bloquent rien
+            if evenement.uid.ends_with(DOMAINE_UID) {
+                ignorees += 1;
+                continue;
+            }
+            // Un événement annulé libère la période importée auparavant
+            if evenement.annule {
+                let avant = self.indisponibilites.len();
+                self.indisponibilites
+                    .retain(|i| !(i.personnel_id == personnel_id && i.uid == evenement.uid));
+                if self.indisponibilites.len() < avant {
+                    supprimees += 1;
+                } else {
+                    ignorees += 1;
+                }
+                continue;
+            }
+            let debut = evenement.debut.format(FORMAT_DATE_HEURE).to_string();
+            let fin = evenement.fin.format(FORMAT_DATE_HEURE).to_string();
+
+            match self
+                .indisponibilites
+                .iter_mut()
+                .find(|i| i.personnel_id == personnel_id && i.uid == evenement.uid)
+            {
+                Some(existante) => {
+                    existante.debut = debut;
+                    existante.fin = fin;
+                    existante.motif = evenement.resume;
+                    mises_a_jour += 1;
+                }
+                None => {
+                    // Les suppressions laissent des trous: l'ID suit le plus grand existant
+                    let id = self
+                        .indisponibilites
+                        .iter()
+                        .map(|i| i.id)
+                        .max()
+                        .unwrap_or(0)
+                        + 1;
+                    self.indisponibilites.push(Indisponibilite {
+                        id,
+                        personnel_id,
+                        uid: evenement.uid,
+                        debut,
+                        fin,
+                        motif: evenement.resume,
+                    });
+                    ajoutees += 1;
+                }
+            }
+        }
+        (ajoutees, mises_a_jour, supprimees, ignorees)
+    }
+
+    // Importe un calendrier externe comme périodes d'indisponibilité
+    pub fn importer_indisponibilites(&mut self) {
+        println!(
+            "{}",
+            "\n=== IMPORT D'INDISPONIBILITÉS (iCALENDAR) ===".green()
+        );
+        self.liste_personnel();
+        let personnel_id = lire_nombre("ID du membre du personnel: ");
+        if !self.personnel.iter().any(|p| p.id == personnel_id) {
+            println!("{}", "Membre du personnel non trouvé!".red());
+            return;
+        }
+
+        let chemin = lire_chaine("Fichier .ics à importer: ");
+        let contenu = match fs::read_to_string(&chemin) {
+            Ok(contenu) => contenu,
+            Err(erreur) => {
+                println!("{}", format!("Erreur de lecture: {}", erreur).red());
+                return;
+            }
+        };
+
+        let (ajoutees, mises_a_jour, supprimees, ignorees) =
+            self.importer_evenements(personnel_id, lire_ics(&contenu));
+
+        self.save_data();
+        println!(
+            "{}",
+            format!(
+                "\n{} période(s) ajoutée(s), {} mise(s) à jour, {} supprimée(s), {} ignorée(s)",
+                ajoutees, mises_a_jour, supprimees, ignorees
+            )
+            .green()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Garde, Horaire, Planning, StatutPersonnel};
+
+    fn date(texte: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(texte, FORMAT_DATE_HEURE).unwrap()
+    }
+
+    #[test]
+    fn aller_retour_export_import() {
+        let evenements = vec![
+            Evenement {
+                uid: format!("rdv-1{}", DOMAINE_UID),
+                debut: date("03/02/2025 09:30"),
+                fin: date("03/02/2025 10:00"),
+                resume: "Consultation Dupont Jean - Dr. Martin Claire".to_string(),
+                description: "Suivi; tension, contrôle\nà jeun".to_string(),
+                annule: false,
+                sequence: 0,
+            },
+            Evenement {
+                uid: format!("rdv-2{}", DOMAINE_UID),
+                debut: date("04/02/2025 14:00"),
+                fin: date("04/02/2025 15:15"),
+                resume:
+                    "Séance de kinésithérapie avec un libellé suffisamment long pour être replié"
+                        .to_string(),
+                description: String::new(),
+                annule: true,
+                sequence: 2,
+            },
+        ];
+
+        let ics = exporter_ics(&evenements);
+        assert!(ics
+            .lines()
+            .all(|l| l.trim_end_matches('\r').len() <= LARGEUR_LIGNE));
+        assert_eq!(lire_ics(&ics), evenements);
+    }
+
+    #[test]
+    fn lecture_dates_journee_et_utc() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:conge-1\r\nDTSTART;VALUE=DATE:20250310\r\nSUMMARY:Congrès\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let evenements = lire_ics(ics);
+        assert_eq!(evenements.len(), 1);
+        assert_eq!(evenements[0].debut, date("10/03/2025 00:00"));
+        assert_eq!(evenements[0].fin, date("11/03/2025 00:00"));
+        assert!(lire_date_ics("", "20250310T080000Z").is_some());
+    }
+
+    #[test]
+    fn import_evenement_externe() {
+        let mut app = Application::new();
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:abc123@agenda.example\r\nDTSTART:20250310T080000\r\nDTEND:20250310T120000\r\nSUMMARY:Formation\\, DPC\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nUID:rdv-7@gestion-hospitaliere\r\nDTSTART:20250310T140000\r\nDTEND:20250310T143000\r\nSUMMARY:Consultation\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        assert_eq!(app.importer_evenements(3, lire_ics(ics)), (1, 0, 0, 1));
+        assert_eq!(app.indisponibilites.len(), 1);
+        let indisponibilite = &app.indisponibilites[0];
+        assert_eq!(indisponibilite.personnel_id, 3);
+        assert_eq!(indisponibilite.uid, "abc123@agenda.example");
+        assert_eq!(indisponibilite.debut, "10/03/2025 08:00");
+        assert_eq!(indisponibilite.fin, "10/03/2025 12:00");
+        assert_eq!(indisponibilite.motif, "Formation, DPC");
+        assert!(indisponibilite.chevauche(3, date("10/03/2025 11:30"), date("10/03/2025 12:30")));
+        assert!(!indisponibilite.chevauche(3, date("10/03/2025 12:00"), date("10/03/2025 12:30")));
+
+        // Le même UID réimporté met la période à jour sans la dupliquer
+        let modifie = ics.replace("DTEND:20250310T120000", "DTEND:20250310T170000");
+        assert_eq!(app.importer_evenements(3, lire_ics(&modifie)), (0, 1, 0, 1));
+        assert_eq!(app.indisponibilites.len(), 1);
+        assert_eq!(app.indisponibilites[0].fin, "10/03/2025 17:00");
+    }
+
+    #[test]
+    fn annulation_evenement_importe() {
+        let mut app = Application::new();
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nUID:abc123@agenda.example\r\nDTSTART:20250310T080000\r\nDTEND:20250310T120000\r\nSUMMARY:Formation\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        assert_eq!(app.importer_evenements(3, lire_ics(ics)), (1, 0, 0, 0));
+
+        // L'organisateur annule: la période importée disparaît
+        let annule = ics.replace("SUMMARY:Formation", "SUMMARY:Formation\r\nSTATUS:CANCELLED");
+        assert_eq!(app.importer_evenements(3, lire_ics(&annule)), (0, 0, 1, 0));
+        assert!(app.indisponibilites.is_empty());
+
+        // Une annulation inconnue est simplement ignorée
+        assert_eq!(app.importer_evenements(3, lire_ics(&annule)), (0, 0, 0, 1));
+    }
+
+    #[test]
+    fn export_vacations_et_gardes() {
+        let mut app = Application::new();
+        app.personnel.push(Personnel {
+            id: 1,
+            nom: "Martin".to_string(),
+            prenom: "Claire".to_string(),
+            specialite: "Cardiologie".to_string(),
+            status: StatutPersonnel::EnService,
+            historique_statut: Vec::new(),
+            planning: Planning {
+                horaires: vec![Horaire {
+                    jour: "Lundi".to_string(),
+                    debut: "08:00".to_string(),
+                    fin: "12:00".to_string(),
+                    service: "Cardiologie".to_string(),
+                }],
+                gardes: vec![Garde {
+                    date: "11/03/2025".to_string(),
+                    service: "Cardiologie".to_string(),
+                }],
+                conges: Vec::new(),
+            },
+            qualifications: Vec::new(),
+        });
+
+        let du = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let au = NaiveDate::from_ymd_opt(2025, 3, 16).unwrap();
+        let evenements = app.evenements_vacations(&app.personnel[0], du, au);
+        assert_eq!(evenements.len(), 2);
+        assert_eq!(
+            evenements[0].uid,
+            format!("vacation-1-20250310T0800{}", DOMAINE_UID)
+        );
+        assert_eq!(evenements[0].fin, date("10/03/2025 12:00"));
+        assert_eq!(
+            evenements[1].uid,
+            format!("garde-1-20250311T2000{}", DOMAINE_UID)
+        );
+        assert_eq!(evenements[1].fin, date("12/03/2025 08:00"));
+
+        // Nos vacations réimportées ne deviennent pas des indisponibilités
+        let relues = lire_ics(&exporter_ics(&evenements));
+        assert_eq!(relues, evenements);
+        assert_eq!(app.importer_evenements(1, relues), (0, 0, 0, 2));
+        assert!(app.indisponibilites.is_empty());
+    }
+}
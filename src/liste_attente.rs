@@ -0,0 +1,369 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::planning::parse_heure;
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date_heure, Application, NiveauUrgence, RendezVous,
+    StatutRendezVous,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CreneauPropose {
+    date: String,
+    heure: String,
+    duree: u32,
+    personnel_id: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum StatutAttente {
+    EnAttente,
+    Proposee(CreneauPropose),
+    Satisfaite(u32),
+    Retiree,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InscriptionAttente {
+    id: u32,
+    patient_id: u32,
+    medecin_id: Option<u32>,
+    specialite: Option<String>,
+    priorite: NiveauUrgence,
+    heure_min: String,
+    heure_max: String,
+    duree: u32,
+    inscription: String,
+    statut: StatutAttente,
+    refus: Vec<CreneauPropose>,
+}
+
+impl InscriptionAttente {
+    // Le créneau convient-il au patient (médecin ou spécialité, plage horaire, durée) ?
+    fn accepte(&self, creneau: &CreneauPropose, specialite: &str) -> bool {
+        let cible = match (&self.medecin_id, &self.specialite) {
+            (Some(medecin_id), _) => *medecin_id == creneau.personnel_id,
+            (None, Some(s)) => s.to_lowercase() == specialite.to_lowercase(),
+            (None, None) => false,
+        };
+        let plage = match (
+            parse_heure(&creneau.heure),
+            parse_heure(&self.heure_min),
+            parse_heure(&self.heure_max),
+        ) {
+            (Some(heure), Some(min), Some(max)) => heure >= min && heure <= max,
+            _ => false,
+        };
+        cible && plage && self.duree <= creneau.duree && !self.refus.contains(creneau)
+    }
+}
+
+impl Application {
+    // Inscription la mieux placée pour un créneau: priorité, puis ancienneté
+    fn meilleur_candidat(&self, creneau: &CreneauPropose) -> Option<u32> {
+        let specialite = self
+            .personnel
+            .iter()
+            .find(|p| p.id == creneau.personnel_id)
+            .map(|p| p.specialite.clone())
+            .unwrap_or_default();
+        let debut = parse_date_heure(&format!("{} {}", creneau.date, creneau.heure))?;
+
+        let mut candidats: Vec<&InscriptionAttente> = self
+            .liste_attente
+            .iter()
+            .filter(|i| i.statut == StatutAttente::EnAttente && i.accepte(creneau, &specialite))
+            .filter(|i| {
+                self.verifier_creneau(debut, i.duree, i.patient_id, creneau.personnel_id, None)
+                    .is_ok()
+            })
+            .collect();
+        candidats.sort_by(|a, b| {
+            b.priorite.cmp(&a.priorite).then_with(|| {
+                parse_date_heure(&a.inscription).cmp(&parse_date_heure(&b.inscription))
+            })
+        });
+        candidats.first().map(|i| i.id)
+    }
+
+    fn proposer_creneau(&mut self, creneau: CreneauPropose) {
+        let inscription_id = match self.meilleur_candidat(&creneau) {
+            Some(id) => id,
+            None => return,
+        };
+        if let Some(inscription) = self
+            .liste_attente
+            .iter_mut()
+            .find(|i| i.id == inscription_id)
+        {
+            let patient_id = inscription.patient_id;
+            inscription.statut = StatutAttente::Proposee(creneau.clone());
+            if let Some(patient) = self.patients.iter().find(|p| p.id == patient_id) {
+                println!(
+                    "{}",
+                    format!(
+                        "Créneau du {} à {} proposé à {} {} (liste d'attente N°{})",
+                        creneau.date, creneau.heure, patient.nom, patient.prenom, inscription_id
+                    )
+                    .yellow()
+                );
+            }
+        }
+    }
+
+    // Appelé après l'annulation d'un rendez-vous pour offrir le créneau libéré
+    pub fn offrir_creneau_libere(&mut self, rdv_id: u32) {
+        let creneau = match self.rendez_vous.iter().find(|r| r.id == rdv_id) {
+            Some(rdv) if rdv.debut() > Some(chrono::Local::now().naive_local()) => CreneauPropose {
+                date: rdv.date.clone(),
+                heure: rdv.heure.clone(),
+                duree: rdv.duree,
+                personnel_id: rdv.personnel_id,
+            },
+            _ => return,
+        };
+        self.proposer_creneau(creneau);
+    }
+
+    pub fn inscrire_liste_attente(&mut self) {
+        println!("{}", "\n=== INSCRIPTION EN LISTE D'ATTENTE ===".green());
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+        if !self.patients.iter().any(|p| p.id == patient_id) {
+            println!("{}", "Patient non trouvé!".red());
+            return;
+        }
+
+        println!("1. Attendre un médecin précis");
+        println!("2. Attendre une spécialité");
+        let (medecin_id, specialite) = match lire_nombre("Choix: ") {
+            1 => {
+                self.liste_personnel();
+                let medecin_id = lire_nombre("ID du médecin: ");
+                if !self.personnel.iter().any(|p| p.id == medecin_id) {
+                    println!("{}", "Médecin non trouvé!".red());
+                    return;
+                }
+                (Some(medecin_id), None)
+            }
+            2 => (None, Some(lire_chaine("Spécialité: "))),
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        println!("Priorité:");
+        println!("1. Faible");
+        println!("2. Moyenne");
+        println!("3. Élevée");
+        println!("4. Critique");
+        let priorite = match lire_nombre("Choix: ") {
+            4 => NiveauUrgence::Critique,
+            3 => NiveauUrgence::Eleve,
+            2 => NiveauUrgence::Moyen,
+            _ => NiveauUrgence::Faible,
+        };
+
+        let heure_min = lire_chaine("Disponible à partir de (HH:MM): ");
+        let heure_max = lire_chaine("Disponible jusqu'à (HH:MM): ");
+        if parse_heure(&heure_min).is_none() || parse_heure(&heure_max).is_none() {
+            println!("{}", "Heure invalide!".red());
+            return;
+        }
+        let duree = lire_nombre("Durée souhaitée (minutes): ");
+
+        self.liste_attente.push(InscriptionAttente {
+            id: (self.liste_attente.len() + 1) as u32,
+            patient_id,
+            medecin_id,
+            specialite,
+            priorite,
+            heure_min,
+            heure_max,
+            duree,
+            inscription: maintenant(),
+            statut: StatutAttente::EnAttente,
+            refus: Vec::new(),
+        });
+        self.save_data();
+        println!("{}", "\nPatient inscrit en liste d'attente!".green());
+    }
+
+    pub fn afficher_liste_attente(&self) {
+        println!("{}", "\n=== LISTE D'ATTENTE ===".green());
+        for inscription in self.liste_attente.iter().filter(|i| {
+            matches!(
+                i.statut,
+                StatutAttente::EnAttente | StatutAttente::Proposee(_)
+            )
+        }) {
+            println!("{}", "-".repeat(40));
+            println!(
+                "Inscription N°{} ({:?})",
+                inscription.id, inscription.priorite
+            );
+            if let Some(patient) = self
+                .patients
+                .iter()
+                .find(|p| p.id == inscription.patient_id)
+            {
+                println!("Patient: {} {}", patient.nom, patient.prenom);
+            }
+            match (&inscription.medecin_id, &inscription.specialite) {
+                (Some(id), _) => {
+                    if let Some(medecin) = self.personnel.iter().find(|p| p.id == *id) {
+                        println!("Médecin: Dr. {} {}", medecin.nom, medecin.prenom);
+                    }
+                }
+                (None, Some(specialite)) => println!("Spécialité: {}", specialite),
+                _ => {}
+            }
+            println!(
+                "Plage souhaitée: {} - {} ({} min)",
+                inscription.heure_min, inscription.heure_max, inscription.duree
+            );
+            println!("Inscrit le: {}", inscription.inscription);
+            if let StatutAttente::Proposee(creneau) = &inscription.statut {
+                println!(
+                    "{}",
+                    format!("Créneau proposé: {} à {}", creneau.date, creneau.heure).yellow()
+                );
+            }
+        }
+    }
+
+    pub fn repondre_proposition(&mut self) {
+        self.afficher_liste_attente();
+        let inscription_id = lire_nombre("ID de l'inscription: ");
+
+        let (patient_id, duree, creneau) =
+            match self.liste_attente.iter().find(|i| i.id == inscription_id) {
+                Some(InscriptionAttente {
+                    patient_id,
+                    duree,
+                    statut: StatutAttente::Proposee(creneau),
+                    ..
+                }) => (*patient_id, *duree, creneau.clone()),
+                _ => {
+                    println!(
+                        "{}",
+                        "Aucune proposition en cours pour cette inscription!".red()
+                    );
+                    return;
+                }
+            };
+
+        println!("1. Confirmer le créneau");
+        println!("2. Refuser et passer au suivant");
+        match lire_nombre("Choix: ") {
+            1 => {
+                let debut = parse_date_heure(&format!("{} {}", creneau.date, creneau.heure));
+                let verification = match debut {
+                    Some(debut) => {
+                        self.verifier_creneau(debut, duree, patient_id, creneau.personnel_id, None)
+                    }
+                    None => Err("Date invalide".to_string()),
+                };
+                if let Err(erreur) = verification {
+                    println!("{}", erreur.red());
+                    return;
+                }
+
+                let rdv_id = (self.rendez_vous.len() + 1) as u32;
+                self.rendez_vous.push(RendezVous {
+                    id: rdv_id,
+                    date: creneau.date,
+                    heure: creneau.heure,
+                    patient_id,
+                    personnel_id: creneau.personnel_id,
+                    duree,
+                    statut: StatutRendezVous::Confirme,
+                    historique: Vec::new(),
+                    remplace: None,
+                    serie_id: None,
+                    a_replanifier: false,
+                });
+                if let Some(inscription) = self
+                    .liste_attente
+                    .iter_mut()
+                    .find(|i| i.id == inscription_id)
+                {
+                    inscription.statut = StatutAttente::Satisfaite(rdv_id);
+                }
+                println!(
+                    "{}",
+                    format!("\nRendez-vous N°{} confirmé!", rdv_id).green()
+                );
+            }
+            2 => {
+                if let Some(inscription) = self
+                    .liste_attente
+                    .iter_mut()
+                    .find(|i| i.id == inscription_id)
+                {
+                    inscription.refus.push(creneau.clone());
+                    inscription.statut = StatutAttente::EnAttente;
+                }
+                println!("Proposition refusée.");
+                self.proposer_creneau(creneau);
+            }
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        }
+        self.save_data();
+    }
+
+    pub fn retirer_liste_attente(&mut self) {
+        self.afficher_liste_attente();
+        let inscription_id = lire_nombre("ID de l'inscription à retirer: ");
+
+        let proposition = match self.liste_attente.iter_mut().find(|i| {
+            i.id == inscription_id
+                && matches!(
+                    i.statut,
+                    StatutAttente::EnAttente | StatutAttente::Proposee(_)
+                )
+        }) {
+            Some(inscription) => {
+                let statut = std::mem::replace(&mut inscription.statut, StatutAttente::Retiree);
+                match statut {
+                    StatutAttente::Proposee(creneau) => Some(creneau),
+                    _ => None,
+                }
+            }
+            None => {
+                println!("{}", "Inscription non trouvée!".red());
+                return;
+            }
+        };
+        // Un créneau en cours de proposition revient au patient suivant
+        if let Some(creneau) = proposition {
+            self.proposer_creneau(creneau);
+        }
+        self.save_data();
+        println!("{}", "\nPatient retiré de la liste d'attente.".green());
+    }
+
+    pub fn menu_liste_attente(&mut self) {
+        loop {
+            println!("\n{}", "=== LISTE D'ATTENTE ===".blue().bold());
+            println!("1. Inscrire un patient");
+            println!("2. Afficher la liste d'attente");
+            println!("3. Répondre à une proposition");
+            println!("4. Retirer un patient");
+            println!("5. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.inscrire_liste_attente(),
+                2 => self.afficher_liste_attente(),
+                3 => self.repondre_proposition(),
+                4 => self.retirer_liste_attente(),
+                5 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
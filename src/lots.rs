@@ -0,0 +1,523 @@
+use chrono::NaiveDate;
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::pharmacie::TypeMouvement;
+use crate::{lire_chaine, maintenant, parse_date, Application, Medicament};
+
+// Numéro attribué au stock antérieur au suivi par lot
+pub const LOT_REPRIS: &str = "SANS-LOT";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Lot {
+    numero: String,
+    quantite: u32,
+    date_peremption: String,
+    fournisseur: String,
+    recu_le: String,
+    #[serde(default)]
+    quarantaine: Option<Quarantaine>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Quarantaine {
+    date: String,
+    utilisateur_id: u32,
+    motif: String,
+}
+
+impl Lot {
+    fn peremption(&self) -> Option<NaiveDate> {
+        parse_date(&self.date_peremption)
+    }
+
+    pub fn numero(&self) -> &str {
+        &self.numero
+    }
+
+    pub fn quantite(&self) -> u32 {
+        self.quantite
+    }
+
+    pub fn date_peremption(&self) -> &str {
+        &self.date_peremption
+    }
+
+    // Négatif une fois le lot périmé
+    pub fn jours_avant_peremption(&self, date: NaiveDate) -> Option<i64> {
+        self.peremption().map(|d| (d - date).num_days())
+    }
+
+    pub fn perime(&self, date: NaiveDate) -> bool {
+        self.peremption().is_some_and(|d| d < date)
+    }
+
+    pub fn en_quarantaine(&self) -> bool {
+        self.quarantaine.is_some()
+    }
+
+    pub fn motif_quarantaine(&self) -> Option<&str> {
+        self.quarantaine.as_ref().map(|q| q.motif.as_str())
+    }
+
+    // Seuls les lots non périmés et hors quarantaine peuvent sortir de la pharmacie
+    fn utilisable(&self, date: NaiveDate) -> bool {
+        !self.perime(date) && !self.en_quarantaine()
+    }
+}
+
+impl Medicament {
+    pub fn lot(&self, numero: &str) -> Option<&Lot> {
+        self.lots.iter().find(|l| l.numero == numero)
+    }
+
+    fn lot_mut(&mut self, numero: &str) -> Option<&mut Lot> {
+        self.lots.iter_mut().find(|l| l.numero == numero)
+    }
+
+    pub fn recalculer_stock(&mut self) {
+        self.stock = self.lots.iter().map(|l| l.quantite).sum();
+    }
+
+    pub fn stock_disponible(&self) -> u32 {
+        self.stock_disponible_le(chrono::Local::now().date_naive())
+    }
+
+    fn stock_disponible_le(&self, date: NaiveDate) -> u32 {
+        self.lots
+            .iter()
+            .filter(|l| l.utilisable(date))
+            .map(|l| l.quantite)
+            .sum()
+    }
+
+    pub fn mettre_en_quarantaine(
+        &mut self,
+        numero: &str,
+        utilisateur_id: u32,
+        motif: String,
+    ) -> Result<(), String> {
+        let lot = self
+            .lot_mut(numero)
+            .filter(|l| l.quantite > 0)
+            .ok_or(format!(
+                "Lot {} inconnu ou épuisé pour ce médicament!",
+                numero
+            ))?;
+        if lot.en_quarantaine() {
+            return Err(format!("Le lot {} est déjà en quarantaine!", numero));
+        }
+        lot.quarantaine = Some(Quarantaine {
+            date: maintenant(),
+            utilisateur_id,
+            motif,
+        });
+        Ok(())
+    }
+
+    // Prélève dans les lots utilisables qui périment en premier (FEFO)
+    pub fn prelever_fefo(&mut self, quantite: u32) -> Result<Vec<(String, u32)>, String> {
+        self.prelever_fefo_le(quantite, chrono::Local::now().date_naive())
+    }
+
+    fn prelever_fefo_le(
+        &mut self,
+        quantite: u32,
+        date: NaiveDate,
+    ) -> Result<Vec<(String, u32)>, String> {
+        let disponible = self.stock_disponible_le(date);
+        if quantite > disponible {
+            return Err(format!(
+                "Stock utilisable insuffisant: {} disponible(s) pour {} (lots périmés ou en quarantaine exclus)",
+                disponible, self.nom
+            ));
+        }
+        // Un lot sans date de péremption lisible passe en dernier
+        self.lots
+            .sort_by_key(|l| (l.peremption().is_none(), l.peremption()));
+        let mut restant = quantite;
+        let mut preleves = Vec::new();
+        for lot in self
+            .lots
+            .iter_mut()
+            .filter(|l| l.quantite > 0 && l.utilisable(date))
+        {
+            if restant == 0 {
+                break;
+            }
+            let pris = restant.min(lot.quantite);
+            lot.quantite -= pris;
+            restant -= pris;
+            preleves.push((lot.numero.clone(), pris));
+        }
+        self.recalculer_stock();
+        Ok(preleves)
+    }
+
+    pub fn prelever_lot(
+        &mut self,
+        numero: &str,
+        quantite: u32,
+    ) -> Result<Vec<(String, u32)>, String> {
+        let lot = self
+            .lot_mut(numero)
+            .ok_or(format!("Lot {} inconnu pour ce médicament!", numero))?;
+        if quantite > lot.quantite {
+            return Err(format!(
+                "Stock insuffisant dans le lot {}: {} disponible(s)",
+                numero, lot.quantite
+            ));
+        }
+        lot.quantite -= quantite;
+        self.recalculer_stock();
+        Ok(vec![(numero.to_string(), quantite)])
+    }
+
+    pub fn crediter_lot(
+        &mut self,
+        numero: &str,
+        quantite: u32,
+    ) -> Result<Vec<(String, u32)>, String> {
+        let lot = self
+            .lot_mut(numero)
+            .ok_or(format!("Lot {} inconnu pour ce médicament!", numero))?;
+        lot.quantite += quantite;
+        self.recalculer_stock();
+        Ok(vec![(numero.to_string(), quantite)])
+    }
+}
+
+impl Application {
+    // Rattache le stock antérieur au suivi par lot à un lot de reprise
+    pub fn reprendre_lots(&mut self) {
+        for medicament in &mut self.pharmacie.medicaments {
+            let en_lots: u32 = medicament.lots.iter().map(|l| l.quantite).sum();
+            if medicament.stock > en_lots {
+                medicament.lots.push(Lot {
+                    numero: LOT_REPRIS.to_string(),
+                    quantite: medicament.stock - en_lots,
+                    date_peremption: medicament.date_peremption.clone(),
+                    fournisseur: "Non renseigné".to_string(),
+                    recu_le: maintenant(),
+                    quarantaine: None,
+                });
+            }
+        }
+        for service in &mut self.services {
+            for ligne in &mut service.armoire {
+                ligne.reprendre_lots();
+            }
+        }
+    }
+
+    // Crée le lot s'il est nouveau; un lot existant est réapprovisionné tel quel
+    pub fn preparer_lot_entrant(
+        &mut self,
+        medicament_id: u32,
+        fournisseur: Option<&str>,
+    ) -> Result<String, String> {
+        let numero = lire_chaine("Numéro de lot: ");
+        if numero.is_empty() {
+            return Err("Numéro de lot obligatoire!".to_string());
+        }
+        let medicament = self
+            .pharmacie
+            .medicaments
+            .iter_mut()
+            .find(|m| m.id == medicament_id)
+            .ok_or("Médicament non trouvé!")?;
+        if let Some(lot) = medicament.lot(&numero) {
+            if let Some(motif) = lot.motif_quarantaine() {
+                return Err(format!(
+                    "Le lot {} est en quarantaine ({}): réception refusée!",
+                    numero, motif
+                ));
+            }
+            println!(
+                "Lot existant: péremption {}, fournisseur {}",
+                lot.date_peremption, lot.fournisseur
+            );
+            return Ok(numero);
+        }
+        let date_peremption = lire_chaine("Date de péremption (JJ/MM/AAAA): ");
+        if parse_date(&date_peremption).is_none() {
+            return Err("Date de péremption invalide!".to_string());
+        }
+        let fournisseur = match fournisseur {
+            Some(nom) => nom.to_string(),
+            None => lire_chaine("Fournisseur: "),
+        };
+        medicament.lots.push(Lot {
+            numero: numero.clone(),
+            quantite: 0,
+            date_peremption,
+            fournisseur,
+            recu_le: maintenant(),
+            quarantaine: None,
+        });
+        Ok(numero)
+    }
+
+    pub fn afficher_lots(&self, medicament: &Medicament) {
+        let aujourd_hui = chrono::Local::now().date_naive();
+        for lot in medicament.lots.iter().filter(|l| l.quantite > 0) {
+            let ligne = format!(
+                "  Lot {} : {} unité(s), péremption {} ({})",
+                lot.numero, lot.quantite, lot.date_peremption, lot.fournisseur
+            );
+            if let Some(quarantaine) = &lot.quarantaine {
+                println!(
+                    "{}",
+                    format!(
+                        "{} - EN QUARANTAINE depuis le {} ({})",
+                        ligne, quarantaine.date, quarantaine.motif
+                    )
+                    .magenta()
+                );
+                continue;
+            }
+            match lot.jours_avant_peremption(aujourd_hui) {
+                Some(jours) if jours < 0 => println!("{} {}", ligne.red(), "- PÉRIMÉ".red()),
+                Some(jours) if jours <= self.pharmacie.delai_alerte_peremption as i64 => println!(
+                    "{}",
+                    format!("{} - périme dans {} jour(s)", ligne, jours).yellow()
+                ),
+                _ => println!("{}", ligne),
+            }
+        }
+    }
+
+    fn nom_service(&self, service_id: u32) -> String {
+        self.services
+            .iter()
+            .find(|s| s.id == service_id)
+            .map(|s| s.nom.clone())
+            .unwrap_or_else(|| format!("Service #{}", service_id))
+    }
+
+    // Rappel de lot: patients et services ayant reçu le lot, y compris les
+    // patients servis depuis l'armoire d'un service
+    pub fn rappel_lot(&self) {
+        println!("{}", "\n=== RAPPEL D'UN LOT ===".green());
+        let numero = lire_chaine("Numéro de lot: ");
+        let mut trouve = false;
+        for mouvement in &self.pharmacie.mouvements {
+            let Some(quantite) = mouvement.quantite_lot(&numero) else {
+                continue;
+            };
+            let medicament = self
+                .pharmacie
+                .medicaments
+                .iter()
+                .find(|m| m.id == mouvement.medicament_id())
+                .map(|m| m.nom.as_str())
+                .unwrap_or("?");
+            match mouvement.type_mouvement() {
+                TypeMouvement::Dispensation { patient_id, .. } => {
+                    let patient = self
+                        .patients
+                        .iter()
+                        .find(|p| p.id == *patient_id)
+                        .map(|p| format!("{} {}", p.prenom, p.nom))
+                        .unwrap_or_else(|| format!("Patient #{}", patient_id));
+                    println!(
+                        "{} - {} : {} unité(s) de {} dispensée(s) à {}",
+                        mouvement.date(),
+                        numero,
+                        quantite,
+                        medicament,
+                        patient
+                    );
+                    trouve = true;
+                }
+                TypeMouvement::Consommation { patient_id } => {
+                    let service = mouvement
+                        .emplacement()
+                        .map(|id| self.nom_service(id))
+                        .unwrap_or_default();
+                    let patient = match patient_id {
+                        Some(id) => self
+                            .patients
+                            .iter()
+                            .find(|p| p.id == *id)
+                            .map(|p| format!(" pour {} {}", p.prenom, p.nom))
+                            .unwrap_or_else(|| format!(" pour le patient #{}", id)),
+                        None => String::new(),
+                    };
+                    println!(
+                        "{} - {} : {} unité(s) de {} consommée(s) dans {}{}",
+                        mouvement.date(),
+                        numero,
+                        quantite,
+                        medicament,
+                        service,
+                        patient
+                    );
+                    trouve = true;
+                }
+                TypeMouvement::SortieService(service_id) => {
+                    let service = self.nom_service(*service_id);
+                    println!(
+                        "{} - {} : {} unité(s) de {} sortie(s) vers {}",
+                        mouvement.date(),
+                        numero,
+                        quantite,
+                        medicament,
+                        service
+                    );
+                    trouve = true;
+                }
+                // Retours en pharmacie centrale (annulation de transfert, retour d'armoire)
+                TypeMouvement::Retour if mouvement.emplacement().is_none() => {
+                    println!(
+                        "{} - {} : {} unité(s) de {} revenue(s) en pharmacie ({})",
+                        mouvement.date(),
+                        numero,
+                        quantite,
+                        medicament,
+                        mouvement.commentaire()
+                    );
+                    trouve = true;
+                }
+                _ => {}
+            }
+        }
+        for (service, medicament_id, quantite) in self.lot_dans_les_armoires(&numero) {
+            println!(
+                "{}",
+                format!(
+                    "Encore en armoire: {} unité(s) de {} dans {}",
+                    quantite,
+                    self.nom_medicament(medicament_id),
+                    service
+                )
+                .yellow()
+            );
+            trouve = true;
+        }
+        if !trouve {
+            println!("{}", "Aucune sortie enregistrée pour ce lot.".yellow());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lot(numero: &str, quantite: u32, date_peremption: &str) -> Lot {
+        Lot {
+            numero: numero.to_string(),
+            quantite,
+            date_peremption: date_peremption.to_string(),
+            fournisseur: "Fournisseur".to_string(),
+            recu_le: "01/01/2025 08:00".to_string(),
+            quarantaine: None,
+        }
+    }
+
+    fn medicament(lots: Vec<Lot>) -> Medicament {
+        let mut medicament = Medicament {
+            id: 1,
+            nom: "Paracétamol".to_string(),
+            description: String::new(),
+            stock: 0,
+            seuil_alerte: 10,
+            date_peremption: String::new(),
+            lots,
+        };
+        medicament.recalculer_stock();
+        medicament
+    }
+
+    fn jour() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn fefo_preleve_le_lot_perimant_en_premier() {
+        let mut m = medicament(vec![
+            lot("B", 10, "01/12/2025"),
+            lot("SANS-DATE", 10, ""),
+            lot("A", 10, "01/09/2025"),
+        ]);
+
+        assert_eq!(
+            m.prelever_fefo_le(4, jour()).unwrap(),
+            vec![("A".to_string(), 4)]
+        );
+        assert_eq!(m.stock, 26);
+    }
+
+    #[test]
+    fn fefo_ignore_les_lots_perimes_et_en_quarantaine() {
+        let mut m = medicament(vec![
+            lot("PERIME", 10, "01/05/2025"),
+            lot("QUARANTAINE", 10, "01/07/2025"),
+            lot("BON", 10, "01/08/2025"),
+        ]);
+        m.mettre_en_quarantaine("QUARANTAINE", 1, "Rappel fournisseur".to_string())
+            .unwrap();
+
+        assert_eq!(m.stock_disponible_le(jour()), 10);
+        assert_eq!(
+            m.prelever_fefo_le(5, jour()).unwrap(),
+            vec![("BON".to_string(), 5)]
+        );
+        assert!(m.prelever_fefo_le(6, jour()).is_err());
+        // Un refus ne touche à aucun lot
+        assert_eq!(m.stock, 25);
+    }
+
+    #[test]
+    fn fefo_enchaine_plusieurs_lots() {
+        let mut m = medicament(vec![
+            lot("C", 10, "01/10/2025"),
+            lot("A", 3, "01/07/2025"),
+            lot("B", 5, "01/08/2025"),
+        ]);
+
+        assert_eq!(
+            m.prelever_fefo_le(12, jour()).unwrap(),
+            vec![
+                ("A".to_string(), 3),
+                ("B".to_string(), 5),
+                ("C".to_string(), 4)
+            ]
+        );
+        assert_eq!(m.stock, 6);
+        assert!(m
+            .lots
+            .iter()
+            .filter(|l| l.numero != "C")
+            .all(|l| l.quantite == 0));
+    }
+
+    #[test]
+    fn reprise_du_stock_anterieur_aux_lots() {
+        let mut app = Application::new();
+        let mut sans_lot = medicament(Vec::new());
+        sans_lot.stock = 40;
+        sans_lot.date_peremption = "01/03/2026".to_string();
+        let mut partiel = medicament(vec![lot("L1", 15, "01/09/2025")]);
+        partiel.id = 2;
+        partiel.stock = 20;
+        app.pharmacie.medicaments = vec![sans_lot, partiel];
+
+        app.reprendre_lots();
+        let [sans_lot, partiel] = &app.pharmacie.medicaments[..] else {
+            panic!("deux médicaments attendus");
+        };
+        assert_eq!(sans_lot.lots.len(), 1);
+        assert_eq!(sans_lot.lots[0].numero, LOT_REPRIS);
+        assert_eq!(sans_lot.lots[0].quantite, 40);
+        assert_eq!(sans_lot.lots[0].date_peremption, "01/03/2026");
+        assert_eq!(partiel.lots.len(), 2);
+        assert_eq!(partiel.lots[1].quantite, 5);
+
+        // Une seconde reprise n'ajoute rien
+        app.reprendre_lots();
+        assert_eq!(app.pharmacie.medicaments[0].lots.len(), 1);
+        assert_eq!(app.pharmacie.medicaments[1].lots.len(), 2);
+    }
+}
@@ -7,8 +7,49 @@ use colored::*;
 
 use std::io::{self, Write};
 
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
+mod armoires;
+mod bloc_operatoire;
+mod commandes;
+mod conformite;
+mod conges;
+mod equipements;
+mod gardes;
+mod hospitalisation;
+mod ical;
+mod liste_attente;
+mod lots;
+mod notifications;
+mod pannes;
+mod peremption;
+mod pharmacie;
+mod planning;
+mod qualifications;
+mod recurrence;
+mod rendez_vous;
+mod reservations;
+mod services;
+mod statut_personnel;
+mod urgences;
+
+use armoires::{DemandeTransfert, StockArmoire};
+use bloc_operatoire::{InterventionChirurgicale, SalleOperation};
+use commandes::{CommandeFournisseur, Fournisseur};
+use conges::DemandeConge;
+use equipements::InterventionMaintenance;
+use hospitalisation::Sejour;
+use ical::Indisponibilite;
+use liste_attente::InscriptionAttente;
+use lots::Lot;
+use notifications::{ModeleRappel, RappelEnvoye};
+use pannes::IncidentEquipement;
+use pharmacie::MouvementStock;
+use qualifications::Qualification;
+use recurrence::SerieRendezVous;
+use reservations::ReservationEquipement;
+use statut_personnel::{ChangementStatutPersonnel, StatutPersonnel};
+use urgences::PassageUrgence;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum NiveauUrgence {
     Faible,
     Moyen,
@@ -52,6 +93,8 @@ pub struct Patient {
     pub numero_secu:String,
     pub dossier_medical: DossierMedical,
     pub niveau_urgence: Option<NiveauUrgence>,
+    #[serde(default)]
+    pub email: Option<String>,
 }
 
 #[derive(Debug,Serialize,Deserialize,Clone)]
@@ -60,12 +103,16 @@ pub struct Personnel {
     pub nom:String,
     pub prenom:String,
     pub specialite:String,
-    pub  status:String,
-    // planning: Planning,
-    qualifications: Vec<String>,
+    pub  status:StatutPersonnel,
+    #[serde(default)]
+    historique_statut: Vec<ChangementStatutPersonnel>,
+    #[serde(default)]
+    planning: Planning,
+    #[serde(default)]
+    qualifications: Vec<Qualification>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 struct Planning {
     horaires: Vec<Horaire>,
     gardes: Vec<Garde>,
@@ -98,39 +145,69 @@ struct Service {
     nom: String,
     chef_service: u32,
     capacite: u32,
+    #[serde(default)]
+    effectif_minimum: u32,
     personnel_affecte: Vec<u32>,
     equipements: Vec<Equipement>,
+    #[serde(default)]
+    qualifications_garde: Vec<String>,
+    #[serde(default)]
+    desactive_le: Option<String>,
+    #[serde(default)]
+    armoire: Vec<StockArmoire>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-struct Equipement {
+pub struct Equipement {
     id: u32,
     nom: String,
     statut: StatutEquipement,
     derniere_maintenance: String,
     prochaine_maintenance: String,
+    #[serde(default)]
+    qualification_requise: Option<String>,
+    #[serde(default)]
+    modele: String,
+    #[serde(default)]
+    intervalle_maintenance: u32,
+    #[serde(default)]
+    interventions: Vec<InterventionMaintenance>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-enum StatutEquipement {
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StatutEquipement {
     Fonctionnel,
     EnMaintenance,
     HorsService,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-struct Medicament {
+pub struct Medicament {
     id: u32,
     nom: String,
     description: String,
     stock: u32,
     seuil_alerte: u32,
+    // Péremption unique antérieure au suivi par lot, reprise dans un lot au chargement
+    #[serde(default)]
     date_peremption: String,
+    #[serde(default)]
+    lots: Vec<Lot>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct Pharmacie {
     medicaments: Vec<Medicament>,
+    #[serde(default)]
+    mouvements: Vec<MouvementStock>,
+    #[serde(default = "peremption::delai_par_defaut")]
+    delai_alerte_peremption: u32,
+    #[serde(default)]
+    fournisseurs: Vec<Fournisseur>,
+    #[serde(default)]
+    commandes: Vec<CommandeFournisseur>,
+    #[serde(default)]
+    transferts: Vec<DemandeTransfert>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -141,6 +218,8 @@ struct Facture {
     total: f64,
     date_emission: String,
     statut: StatutFacture,
+    #[serde(default)]
+    rendez_vous_id: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -152,6 +231,7 @@ struct Prestation {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 enum StatutFacture {
+    Brouillon,
     EnAttente,
     Payee,
     Annulee,
@@ -164,7 +244,38 @@ pub struct RendezVous {
     pub date:String,
     pub heure:String,
     pub patient_id :u32,
-    pub personnel_id:u32
+    pub personnel_id:u32,
+    #[serde(default = "rendez_vous::duree_par_defaut")]
+    pub duree:u32,
+    #[serde(default)]
+    pub statut: StatutRendezVous,
+    #[serde(default)]
+    pub historique: Vec<ChangementStatut>,
+    #[serde(default)]
+    pub remplace: Option<u32>,
+    #[serde(default)]
+    pub serie_id: Option<u32>,
+    #[serde(default)]
+    pub a_replanifier: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum StatutRendezVous {
+    #[default]
+    Planifie,
+    Confirme,
+    Arrive,
+    Termine,
+    Annule,
+    Absent,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangementStatut {
+    pub date: String,
+    pub ancien: StatutRendezVous,
+    pub nouveau: StatutRendezVous,
+    pub motif: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -194,7 +305,36 @@ pub  struct Application{
     pharmacie: Pharmacie,
     factures: Vec<Facture>,
     utilisateurs: Vec<Utilisateur>,
+    #[serde(default)]
+    urgences: Vec<PassageUrgence>,
+    #[serde(default)]
+    sejours: Vec<Sejour>,
+    #[serde(default)]
+    series: Vec<SerieRendezVous>,
+    #[serde(default)]
+    liste_attente: Vec<InscriptionAttente>,
+    #[serde(default)]
+    indisponibilites: Vec<Indisponibilite>,
+    #[serde(default)]
+    modeles_rappel: Vec<ModeleRappel>,
+    #[serde(default)]
+    rappels_envoyes: Vec<RappelEnvoye>,
+    #[serde(default)]
+    demandes_conge: Vec<DemandeConge>,
+    #[serde(default)]
+    reservations_equipement: Vec<ReservationEquipement>,
+    #[serde(default)]
+    incidents_equipement: Vec<IncidentEquipement>,
+    #[serde(default)]
+    salles_operation: Vec<SalleOperation>,
+    #[serde(default)]
+    interventions_chirurgicales: Vec<InterventionChirurgicale>,
+}
 
+impl Default for Application {
+    fn default() -> Self {
+        Application::new()
+    }
 }
 
 
@@ -206,10 +346,28 @@ impl Application {
             personnel:Vec::new(),
             rendez_vous:Vec::new(),
             services:Vec::new(),
-            pharmacie: Pharmacie { medicaments: Vec::new() },
+            pharmacie: Pharmacie {
+                medicaments: Vec::new(),
+                mouvements: Vec::new(),
+                delai_alerte_peremption: peremption::delai_par_defaut(),
+                fournisseurs: Vec::new(),
+                commandes: Vec::new(),
+                transferts: Vec::new(),
+            },
             factures:Vec::new(),
             utilisateurs:Vec::new(),
-            
+            urgences:Vec::new(),
+            sejours:Vec::new(),
+            series:Vec::new(),
+            liste_attente:Vec::new(),
+            indisponibilites:Vec::new(),
+            modeles_rappel:Vec::new(),
+            rappels_envoyes:Vec::new(),
+            demandes_conge:Vec::new(),
+            reservations_equipement:Vec::new(),
+            incidents_equipement:Vec::new(),
+            salles_operation:Vec::new(),
+            interventions_chirurgicales:Vec::new(),
         }
     }
 
@@ -265,6 +423,7 @@ impl Application {
         let prenom = lire_chaine("Prenom: ");
         let date_naissance = lire_chaine("Date de naissance (JJ/MM/AAAA): ");
         let numero_secu = lire_chaine("Numéro de sécurité sociale: ");
+        let email = Some(lire_chaine("E-mail (facultatif): ")).filter(|e| !e.is_empty());
 
         let dossier_medical = DossierMedical {
             antecedents: Vec::new(),
@@ -275,7 +434,7 @@ impl Application {
         };
 
         let patient = Patient{
-            id,nom,prenom,date_naissance,numero_secu, dossier_medical, niveau_urgence:None
+            id,nom,prenom,date_naissance,numero_secu, dossier_medical, niveau_urgence:None, email
         };
         self.patients.push(patient);
         self.save_data();
@@ -301,7 +460,7 @@ impl Application {
         let nom = lire_chaine("Nom: ");
         let prenom = lire_chaine("prenom: ");
         let specialite = lire_chaine("Spécialité: ");
-        let status = String::from("En service");
+        let status = StatutPersonnel::EnService;
         let qualifications = Vec::new();
 
         let personnel = Personnel {
@@ -310,6 +469,8 @@ impl Application {
             prenom,
             specialite,
             status,
+            historique_statut: Vec::new(),
+            planning: Planning::default(),
             qualifications,
         };
 
@@ -325,7 +486,11 @@ impl Application {
             println!("ID: {}", pers.id);
             println!("Dr. {} {}", pers.nom, pers.prenom);
             println!("Spécialité: {}", pers.specialite);
-            println!("Status: {}", pers.status);
+            println!("Status: {}", pers.status.libelle());
+            if !pers.qualifications.is_empty() {
+                let intitules: Vec<&str> = pers.qualifications.iter().map(|q| q.intitule()).collect();
+                println!("Qualifications: {}", intitules.join(", "));
+            }
         }
     }
 
@@ -333,16 +498,53 @@ impl Application {
         println!("{}", "\n=== NOUVEAU RENDEZ-VOUS ===".green());
         
         let id = (self.rendez_vous.len() + 1) as u32;
-        let date = lire_chaine("Date (JJ/MM/AAAA): ");
-        let heure = lire_chaine("Heure (HH:MM): ");
-        
+
         self.liste_patients();
         let patient_id = lire_nombre("ID du patient: ");
+        if !self.patients.iter().any(|p| p.id == patient_id) {
+            println!("{}", "Patient non trouvé!".red());
+            return;
+        }
 
         self.liste_personnel();
         let personnel_id = lire_nombre("ID du médecin: ");
+        if !self.personnel.iter().any(|p| p.id == personnel_id) {
+            println!("{}", "Médecin non trouvé!".red());
+            return;
+        }
+
+        let date = lire_chaine("Date (JJ/MM/AAAA): ");
+        let heure = lire_chaine("Heure (HH:MM): ");
+        let duree = lire_nombre("Durée (minutes): ");
+
+        let debut = match parse_date_heure(&format!("{} {}", date, heure)) {
+            Some(debut) => debut,
+            None => {
+                println!("{}", "Date ou heure invalide!".red());
+                return;
+            }
+        };
 
-        let rdv = RendezVous{id,date,heure,patient_id,personnel_id};
+        if let Err(erreur) = self.verifier_creneau(debut, duree, patient_id, personnel_id, None) {
+            println!("{}", erreur.red());
+            println!("\nProchains créneaux libres pour ce médecin:");
+            self.proposer_creneaux(personnel_id, duree);
+            return;
+        }
+
+        let absences = self.absences_patient(patient_id);
+        if absences > 0 {
+            println!("{}", format!("⚠️ Ce patient a {} absence(s) non excusée(s).", absences).yellow());
+        }
+
+        let rdv = RendezVous{
+            id,date,heure,patient_id,personnel_id,duree,
+            statut: StatutRendezVous::Planifie,
+            historique: Vec::new(),
+            remplace: None,
+            serie_id: None,
+            a_replanifier: false,
+        };
 
         self.rendez_vous.push(rdv);
         self.save_data();
@@ -355,15 +557,71 @@ impl Application {
         for rdv in &self.rendez_vous {
             println!("{}", "-".repeat(40));
             let patient = self.patients.iter().find(|p| p.id == rdv.patient_id).unwrap();
-            let medecin = self.personnel.iter().find(|p| p.id == rdv.personnel_id).unwrap();
             println!("ID: {}", rdv.id);
-            println!("Date: {} à {}", rdv.date, rdv.heure);
+            println!("Date: {} à {} ({} min)", rdv.date, rdv.heure, rdv.duree);
             println!("Patient: {} {}", patient.nom, patient.prenom);
-            println!("Médecin: Dr. {} {}", medecin.nom, medecin.prenom);
+            println!("Médecin: {}", self.nom_personnel(rdv.personnel_id));
+            println!("Statut: {:?}", rdv.statut);
+            if rdv.a_replanifier {
+                println!("{}", "⚠️ À replanifier (médecin absent)".red());
+            }
+            if let Some(serie_id) = rdv.serie_id {
+                println!("Série N°{}", serie_id);
+            }
+            if let Some(origine) = rdv.remplace {
+                println!("Remplace le rendez-vous N°{}", origine);
+            }
+            if let Some(changement) = rdv.historique.last() {
+                if !changement.motif.is_empty() {
+                    println!("Motif: {}", changement.motif);
+                }
+            }
         }
     }
 
-    
+    fn prescrire_traitement(&mut self) {
+        println!("{}", "\n=== PRESCRIPTION D'UN TRAITEMENT ===".green());
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+        if !self.patients.iter().any(|p| p.id == patient_id) {
+            println!("{}", "Patient non trouvé!".red());
+            return;
+        }
+        self.verifier_stock();
+        let medicament_id = lire_nombre("ID du médicament: ");
+        let medicament = match self.pharmacie.medicaments.iter().find(|m| m.id == medicament_id) {
+            Some(med) => med.nom.clone(),
+            None => {
+                println!("{}", "Médicament non trouvé!".red());
+                return;
+            }
+        };
+        let posologie = lire_chaine("Posologie: ");
+        let date_debut = lire_chaine("Date de début (JJ/MM/AAAA): ");
+        let date_fin = Some(lire_chaine("Date de fin (JJ/MM/AAAA, Entrée si non définie): ")).filter(|d| !d.is_empty());
+        if parse_date(&date_debut).is_none() || date_fin.as_deref().is_some_and(|d| parse_date(d).is_none()) {
+            println!("{}", "Date invalide!".red());
+            return;
+        }
+        let prescrit_par = lire_nombre("ID du médecin prescripteur: ");
+        if !self.personnel.iter().any(|p| p.id == prescrit_par && p.actif()) {
+            println!("{}", "Médecin non trouvé ou parti!".red());
+            return;
+        }
+
+        if let Some(patient) = self.patients.iter_mut().find(|p| p.id == patient_id) {
+            patient.dossier_medical.traitements.push(Traitement {
+                medicament,
+                posologie,
+                date_debut,
+                date_fin,
+                prescrit_par,
+            });
+        }
+        self.save_data();
+        println!("{}", "\nTraitement prescrit avec succès!".green());
+    }
+
     pub fn ajouter_note_medicale(&mut self) {
         self.liste_patients();
         let patient_id = lire_nombre("ID du patient: ");
@@ -373,6 +631,10 @@ impl Application {
             let date = chrono::Local::now().format("%d/%m/%Y").to_string();
             let contenu = lire_chaine("Contenu de la note: ");
             let auteur = lire_nombre("ID du médecin: ");
+            if !self.personnel.iter().any(|p| p.id == auteur && p.actif()) {
+                println!("{}", "Médecin non trouvé ou parti!".red());
+                return;
+            }
 
             let note = NoteMedicale {
                 date,
@@ -388,6 +650,49 @@ impl Application {
         }
     }
 
+    fn afficher_dossier_medical(&self) {
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+        let patient = match self.patients.iter().find(|p| p.id == patient_id) {
+            Some(patient) => patient,
+            None => {
+                println!("{}", "Patient non trouvé!".red());
+                return;
+            }
+        };
+
+        println!("{}", format!("\n=== DOSSIER MÉDICAL DE {} {} ===", patient.nom, patient.prenom).green());
+        let dossier = &patient.dossier_medical;
+        if !dossier.groupe_sanguin.is_empty() {
+            println!("Groupe sanguin: {}", dossier.groupe_sanguin);
+        }
+        if !dossier.allergies.is_empty() {
+            println!("Allergies: {}", dossier.allergies.join(", "));
+        }
+        if !dossier.antecedents.is_empty() {
+            println!("Antécédents: {}", dossier.antecedents.join(", "));
+        }
+
+        println!("\n--- Traitements ---");
+        for (numero, traitement) in dossier.traitements.iter().enumerate() {
+            println!(
+                "{}. {} - {} du {} au {} (prescrit par {})",
+                numero + 1,
+                traitement.medicament,
+                traitement.posologie,
+                traitement.date_debut,
+                traitement.date_fin.as_deref().unwrap_or("..."),
+                self.nom_personnel(traitement.prescrit_par)
+            );
+        }
+        println!("\n--- Notes ---");
+        for note in &dossier.notes {
+            println!("{}", "-".repeat(40));
+            println!("{} - {}", note.date, self.nom_personnel(note.auteur));
+            println!("{}", note.contenu);
+        }
+    }
+
     // Gestion de la pharmacie
     fn ajouter_medicament(&mut self) {
         println!("{}", "\n=== AJOUT D'UN MÉDICAMENT ===".green());
@@ -396,18 +701,23 @@ impl Application {
         let description = lire_chaine("Description: ");
         let stock = lire_nombre("Quantité en stock: ");
         let seuil_alerte = lire_nombre("Seuil d'alerte: ");
-        let date_peremption = lire_chaine("Date de péremption (JJ/MM/AAAA): ");
 
         let medicament = Medicament {
             id,
             nom,
             description,
-            stock,
+            stock: 0,
             seuil_alerte,
-            date_peremption,
+            date_peremption: String::new(),
+            lots: Vec::new(),
         };
 
         self.pharmacie.medicaments.push(medicament);
+        if stock > 0 {
+            if let Err(erreur) = self.entree_initiale(id, stock) {
+                println!("{}", erreur.red());
+            }
+        }
         self.save_data();
         println!("{}", "\nMédicament ajouté avec succès!".green());
     }
@@ -417,11 +727,19 @@ impl Application {
         for med in &self.pharmacie.medicaments {
             println!("{}", "-".repeat(40));
             println!("Médicament: {}", med.nom);
+            println!("ID: {}", med.id);
             println!("Stock: {}", med.stock);
-            if med.stock <= med.seuil_alerte {
+            if med.stock_disponible() < med.stock {
+                println!("Dont utilisable: {}", med.stock_disponible());
+            }
+            if med.stock_disponible() <= med.seuil_alerte {
                 println!("{}", "⚠️ Stock faible!".red());
+                match self.quantite_en_commande(med.id) {
+                    0 => println!("Aucune commande en cours, voir les suggestions de réapprovisionnement"),
+                    en_commande => println!("En commande: {}", en_commande),
+                }
             }
-            println!("Péremption: {}", med.date_peremption);
+            self.afficher_lots(med);
         }
     }
 
@@ -430,16 +748,34 @@ impl Application {
         println!("{}", "\n=== AJOUT D'UN SERVICE ===".green());
         let id = (self.services.len() + 1) as u32;
         let nom = lire_chaine("Nom du service: ");
+        if nom.is_empty() || self.services.iter().any(|s| s.nom.eq_ignore_ascii_case(&nom)) {
+            println!("{}", "Nom de service vide ou déjà utilisé!".red());
+            return;
+        }
+        self.liste_personnel();
         let chef_service = lire_nombre("ID du chef de service: ");
+        if let Err(erreur) = self.verifier_chef_service(chef_service) {
+            println!("{}", erreur.red());
+            return;
+        }
         let capacite = lire_nombre("Capacité d'accueil: ");
+        if capacite == 0 {
+            println!("{}", "La capacité doit être positive!".red());
+            return;
+        }
+        let effectif_minimum = lire_nombre("Effectif minimum par jour: ");
 
         let service = Service {
             id,
             nom,
             chef_service,
             capacite,
-            personnel_affecte: Vec::new(),
+            effectif_minimum,
+            personnel_affecte: vec![chef_service],
             equipements: Vec::new(),
+            qualifications_garde: Vec::new(),
+            desactive_le: None,
+            armoire: Vec::new(),
         };
 
         self.services.push(service);
@@ -482,6 +818,7 @@ impl Application {
             total,
             date_emission,
             statut: StatutFacture::EnAttente,
+            rendez_vous_id: None,
         };
 
         self.factures.push(facture);
@@ -507,6 +844,15 @@ impl Application {
             .filter(|r| r.date == chrono::Local::now().format("%d/%m/%Y").to_string())
             .count();
         println!("Rendez-vous aujourd'hui: {}", rdv_aujourdhui);
+        let rdv_passes = self.rendez_vous.iter()
+            .filter(|r| matches!(r.statut, StatutRendezVous::Termine | StatutRendezVous::Absent))
+            .count();
+        if rdv_passes > 0 {
+            let absents = self.rendez_vous.iter()
+                .filter(|r| r.statut == StatutRendezVous::Absent)
+                .count();
+            println!("Taux d'absence: {:.1}%", absents as f64 * 100.0 / rdv_passes as f64);
+        }
         
         // Statistiques financières
         println!("\n--- Finances ---");
@@ -518,9 +864,16 @@ impl Application {
         // Statistiques de la pharmacie
         println!("\n--- Pharmacie ---");
         let medicaments_alerte = self.pharmacie.medicaments.iter()
-            .filter(|m| m.stock <= m.seuil_alerte)
+            .filter(|m| m.stock_disponible() <= m.seuil_alerte)
             .count();
         println!("Médicaments en alerte stock: {}", medicaments_alerte);
+        self.statistiques_peremption();
+
+        // Statistiques des urgences
+        self.statistiques_urgences();
+
+        // Statistiques d'hospitalisation
+        self.statistiques_hospitalisation();
         
         println!("{}", "-".repeat(40));
     }
@@ -531,14 +884,20 @@ impl Application {
             println!("\n{}", "=== GESTION DES PATIENTS ===".blue().bold());
             println!("1. Ajouter un patient");
             println!("2. Liste des patients");
-            println!("3. Retour");
+            println!("3. Ajouter une note médicale");
+            println!("4. Prescrire un traitement");
+            println!("5. Dossier médical d'un patient");
+            println!("6. Retour");
             print!("\nChoix: ");
             io::stdout().flush().unwrap();
 
             match lire_nombre("") {
                 1 => self.ajouter_patient(),
                 2 => self.liste_patients(),
-                3 => break,
+                3 => self.ajouter_note_medicale(),
+                4 => self.prescrire_traitement(),
+                5 => self.afficher_dossier_medical(),
+                6 => break,
                 _ => println!("{}", "Choix invalide!".red()),
             }
         }
@@ -549,14 +908,20 @@ impl Application {
             println!("\n{}", "=== GESTION DU PERSONNEL ===".blue().bold());
             println!("1. Ajouter un membre du personnel");
             println!("2. Liste du personnel");
-            println!("3. Retour");
+            println!("3. Planning du personnel");
+            println!("4. Qualifications");
+            println!("5. Statut du personnel");
+            println!("6. Retour");
             print!("\nChoix: ");
             io::stdout().flush().unwrap();
 
             match lire_nombre("") {
                 1 => self.ajouter_personnel(),
                 2 => self.liste_personnel(),
-                3 => break,
+                3 => self.menu_planning(),
+                4 => self.menu_qualifications(),
+                5 => self.menu_statut_personnel(),
+                6 => break,
                 _ => println!("{}", "Choix invalide!".red()),
             }
         }
@@ -567,14 +932,36 @@ impl Application {
             println!("\n{}", "=== GESTION DES RENDEZ-VOUS ===".blue().bold());
             println!("1. Nouveau rendez-vous");
             println!("2. Liste des rendez-vous");
-            println!("3. Retour");
+            println!("3. Rechercher un créneau libre");
+            println!("4. Changer le statut d'un rendez-vous");
+            println!("5. Déplacer un rendez-vous");
+            println!("6. Absences par patient");
+            println!("7. Créer une série de rendez-vous");
+            println!("8. Modifier ou annuler une série");
+            println!("9. Liste d'attente");
+            println!("10. Exporter un calendrier (iCalendar)");
+            println!("11. Importer des indisponibilités (iCalendar)");
+            println!("12. Envoyer les rappels J-2 / J-1");
+            println!("13. Modèles de rappel");
+            println!("14. Retour");
             print!("\nChoix: ");
             io::stdout().flush().unwrap();
 
             match lire_nombre("") {
                 1 => self.ajouter_rendez_vous(),
                 2 => self.liste_rendez_vous(),
-                3 => break,
+                3 => self.rechercher_creneau(),
+                4 => self.changer_statut_rendez_vous(),
+                5 => self.deplacer_rendez_vous(),
+                6 => self.liste_absences(),
+                7 => self.creer_serie_rendez_vous(),
+                8 => self.modifier_serie_rendez_vous(),
+                9 => self.menu_liste_attente(),
+                10 => self.exporter_calendrier(),
+                11 => self.importer_indisponibilites(),
+                12 => self.lancer_rappels(),
+                13 => self.gerer_modeles_rappel(),
+                14 => break,
                 _ => println!("{}", "Choix invalide!".red()),
             }
         }
@@ -592,7 +979,10 @@ impl Application {
             println!("6. Gestion des Factures");
             println!("7. Administration");
             println!("8. Statistiques");
-            println!("9. Quitter");
+            println!("9. Urgences");
+            println!("10. Hospitalisation");
+            println!("11. Bloc opératoire");
+            println!("12. Quitter");
             
             match lire_nombre("\nChoix: ") {
                 1 => self.menu_patients(),
@@ -603,7 +993,10 @@ impl Application {
                 6 => self.menu_factures(),
                 7 => self.menu_admin(),
                 8 => self.afficher_statistiques(),
-                9 => break,
+                9 => self.menu_urgences(),
+                10 => self.menu_hospitalisation(),
+                11 => self.menu_bloc_operatoire(),
+                12 => break,
                 _ => println!("{}", "Choix invalide!".red()),
             }
         }
@@ -614,12 +1007,22 @@ impl Application {
             println!("\n{}", "=== GESTION DE LA PHARMACIE ===".blue().bold());
             println!("1. Ajouter un médicament");
             println!("2. Vérifier les stocks");
-            println!("3. Retour");
+            println!("3. Mouvements de stock");
+            println!("4. Rappel d'un lot");
+            println!("5. Péremptions et quarantaine");
+            println!("6. Fournisseurs et commandes");
+            println!("7. Stocks des services");
+            println!("8. Retour");
             
             match lire_nombre("\nChoix: ") {
                 1 => self.ajouter_medicament(),
                 2 => self.verifier_stock(),
-                3 => break,
+                3 => self.menu_mouvements_stock(),
+                4 => self.rappel_lot(),
+                5 => self.menu_peremption(),
+                6 => self.menu_commandes(),
+                7 => self.menu_armoires(),
+                8 => break,
                 _ => println!("{}", "Choix invalide!".red()),
             }
         }
@@ -697,12 +1100,17 @@ impl Application {
             println!("{}", "-".repeat(40));
             println!("ID: {}", service.id);
             println!("Nom: {}", service.nom);
-            if let Some(chef) = self.personnel.iter().find(|p| p.id == service.chef_service) {
-                println!("Chef de service: Dr. {} {}", chef.nom, chef.prenom);
+            if let Some(date) = &service.desactive_le {
+                println!("{}", format!("Désactivé le {}", date).red());
             }
+            println!("Chef de service: {}", self.nom_personnel(service.chef_service));
             println!("Capacité: {}", service.capacite);
+            println!("Effectif minimum: {}", service.effectif_minimum);
             println!("Personnel affecté: {}", service.personnel_affecte.len());
             println!("Équipements: {}", service.equipements.len());
+            if !service.qualifications_garde.is_empty() {
+                println!("Qualifications requises pour la garde: {}", service.qualifications_garde.join(", "));
+            }
         }
     }
 
@@ -739,12 +1147,16 @@ impl Application {
             println!("\n{}", "=== GESTION DES SERVICES ===".blue().bold());
             println!("1. Ajouter un service");
             println!("2. Liste des services");
-            println!("3. Retour");
+            println!("3. Modifier un service");
+            println!("4. Équipements");
+            println!("5. Retour");
             
             match lire_nombre("\nChoix: ") {
                 1 => self.ajouter_service(),
                 2 => self.liste_services(),
-                3 => break,
+                3 => self.menu_gestion_service(),
+                4 => self.menu_equipements(),
+                5 => break,
                 _ => println!("{}", "Choix invalide!".red()),
             }
         }
@@ -755,6 +1167,21 @@ impl Application {
 }
 
 // Fonctions utilitaires
+const FORMAT_DATE: &str = "%d/%m/%Y";
+const FORMAT_DATE_HEURE: &str = "%d/%m/%Y %H:%M";
+
+fn maintenant() -> String {
+    chrono::Local::now().format(FORMAT_DATE_HEURE).to_string()
+}
+
+fn parse_date(valeur: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(valeur, FORMAT_DATE).ok()
+}
+
+fn parse_date_heure(valeur: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(valeur, FORMAT_DATE_HEURE).ok()
+}
+
 fn lire_chaine(message: &str) -> String {
     print!("{}", message);
     io::stdout().flush().unwrap();
@@ -776,6 +1203,12 @@ pub fn lire_nombre(message: &str) -> u32 {
 fn main() {
     let mut app = Application::charger_data();
     println!("{}", "Bienvenue dans le système de gestion hospitalière!".green().bold());
+    app.appliquer_statuts_echus();
+    app.alertes_qualifications();
+    app.alertes_maintenance();
+    app.reprendre_lots();
+    app.alertes_peremption();
+    app.alertes_armoires();
     app.menu_principal();
     println!("{}", "Au revoir!".green().bold());
 }
\ No newline at end of file
@@ -0,0 +1,513 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+
+use chrono::{Duration, NaiveDate};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date, Application, RendezVous, StatutRendezVous,
+};
+
+// Nombre de jours avant le rendez-vous auxquels un rappel est envoyé
+const ECHEANCES_RAPPEL: [i64; 2] = [2, 1];
+const TYPE_PAR_DEFAUT: &str = "consultation";
+const DOSSIER_SPOOL: &str = "spool";
+
+pub struct Message {
+    pub id: String,
+    pub destinataire: String,
+    pub sujet: String,
+    pub corps: String,
+}
+
+pub trait Notificateur {
+    fn envoyer(&mut self, message: &Message) -> Result<(), String>;
+
+    // Faux pour un canal local (boîte d'envoi) qui n'a pas besoin d'adresse
+    fn exige_destinataire(&self) -> bool {
+        true
+    }
+}
+
+// Écrit chaque message dans un fichier du dossier de spool
+pub struct BoiteEnvoi {
+    dossier: PathBuf,
+}
+
+impl BoiteEnvoi {
+    pub fn new(dossier: impl Into<PathBuf>) -> Self {
+        BoiteEnvoi {
+            dossier: dossier.into(),
+        }
+    }
+}
+
+impl Notificateur for BoiteEnvoi {
+    fn envoyer(&mut self, message: &Message) -> Result<(), String> {
+        fs::create_dir_all(&self.dossier).map_err(|e| e.to_string())?;
+        let contenu = format!(
+            "To: {}\nSubject: {}\n\n{}\n",
+            message.destinataire, message.sujet, message.corps
+        );
+        fs::write(self.dossier.join(format!("{}.txt", message.id)), contenu)
+            .map_err(|e| e.to_string())
+    }
+
+    fn exige_destinataire(&self) -> bool {
+        false
+    }
+}
+
+// Client SMTP minimal, sans chiffrement ni authentification (relais local)
+pub struct Smtp {
+    serveur: String,
+    expediteur: String,
+}
+
+impl Smtp {
+    pub fn new(serveur: &str, expediteur: &str) -> Self {
+        Smtp {
+            serveur: serveur.to_string(),
+            expediteur: expediteur.to_string(),
+        }
+    }
+}
+
+fn lire_reponse(lecteur: &mut impl BufRead, attendu: &str) -> Result<(), String> {
+    loop {
+        let mut ligne = String::new();
+        lecteur.read_line(&mut ligne).map_err(|e| e.to_string())?;
+        if !ligne.starts_with(attendu) {
+            return Err(format!("Réponse SMTP inattendue: {}", ligne.trim_end()));
+        }
+        // "250-..." annonce une réponse sur plusieurs lignes
+        if ligne.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+impl Notificateur for Smtp {
+    fn envoyer(&mut self, message: &Message) -> Result<(), String> {
+        let flux = TcpStream::connect(&self.serveur).map_err(|e| e.to_string())?;
+        let mut lecteur = BufReader::new(flux.try_clone().map_err(|e| e.to_string())?);
+        let mut ecrivain = flux;
+
+        let mut commande = |ligne: &str, attendu: &str, lecteur: &mut BufReader<TcpStream>| {
+            write!(ecrivain, "{}\r\n", ligne).map_err(|e| e.to_string())?;
+            lire_reponse(lecteur, attendu)
+        };
+
+        lire_reponse(&mut lecteur, "220")?;
+        commande("EHLO gestion-hospitaliere", "250", &mut lecteur)?;
+        commande(
+            &format!("MAIL FROM:<{}>", self.expediteur),
+            "250",
+            &mut lecteur,
+        )?;
+        commande(
+            &format!("RCPT TO:<{}>", message.destinataire),
+            "25",
+            &mut lecteur,
+        )?;
+        commande("DATA", "354", &mut lecteur)?;
+
+        // Les lignes commençant par un point sont doublées (RFC 5321, 4.5.2)
+        let corps: Vec<String> = message
+            .corps
+            .lines()
+            .map(|l| {
+                if l.starts_with('.') {
+                    format!(".{}", l)
+                } else {
+                    l.to_string()
+                }
+            })
+            .collect();
+        let donnees = format!(
+            "From: <{}>\r\nTo: <{}>\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n.",
+            self.expediteur,
+            message.destinataire,
+            encoder_sujet(&message.sujet),
+            corps.join("\r\n")
+        );
+        commande(&donnees, "250", &mut lecteur)?;
+        commande("QUIT", "221", &mut lecteur)
+    }
+}
+
+// Échéance à rappeler pour un rendez-vous le `date`: la plus proche dont le
+// jour est atteint (date - N <= aujourd'hui < date). Une échéance plus
+// lointaine manquée n'est pas rattrapée une fois la suivante atteinte.
+fn echeance_due(
+    date: NaiveDate,
+    aujourdhui: NaiveDate,
+    deja_envoye: impl Fn(i64) -> bool,
+) -> Option<i64> {
+    if aujourdhui >= date {
+        return None;
+    }
+    ECHEANCES_RAPPEL
+        .iter()
+        .copied()
+        .filter(|&jours| date - Duration::days(jours) <= aujourdhui)
+        .min()
+        .filter(|&jours| !deja_envoye(jours))
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(octets: &[u8]) -> String {
+    let mut texte = String::new();
+    for bloc in octets.chunks(3) {
+        let n = bloc
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &o)| n | (o as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= bloc.len() {
+                texte.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                texte.push('=');
+            }
+        }
+    }
+    texte
+}
+
+// En-tête Subject encodé selon la RFC 2047 s'il contient des caractères non
+// ASCII; chaque mot encodé reste sous 75 caractères et les mots sont repliés.
+fn encoder_sujet(sujet: &str) -> String {
+    if sujet.is_ascii() {
+        return sujet.to_string();
+    }
+    let mut mots = Vec::new();
+    let mut morceau = String::new();
+    for c in sujet.chars() {
+        // 45 octets donnent 60 caractères en base64
+        if morceau.len() + c.len_utf8() > 45 {
+            mots.push(format!("=?UTF-8?B?{}?=", base64(morceau.as_bytes())));
+            morceau.clear();
+        }
+        morceau.push(c);
+    }
+    mots.push(format!("=?UTF-8?B?{}?=", base64(morceau.as_bytes())));
+    mots.join("\r\n ")
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModeleRappel {
+    type_rdv: String,
+    sujet: String,
+    corps: String,
+}
+
+impl ModeleRappel {
+    fn par_defaut() -> ModeleRappel {
+        ModeleRappel {
+            type_rdv: TYPE_PAR_DEFAUT.to_string(),
+            sujet: "Rappel: rendez-vous du {date}".to_string(),
+            corps: "Bonjour {patient},\nNous vous rappelons votre rendez-vous ({type}) le {date} à {heure} avec Dr. {medecin}.\nEn cas d'empêchement, merci de prévenir le secrétariat.".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RappelEnvoye {
+    rdv_id: u32,
+    jours_avant: i64,
+    date_envoi: String,
+}
+
+impl Application {
+    // Type d'un rendez-vous: le libellé de sa série, sinon une consultation simple
+    fn type_rendez_vous(&self, rdv: &RendezVous) -> String {
+        rdv.serie_id
+            .and_then(|id| self.series.iter().find(|s| s.id() == id))
+            .map(|s| s.libelle().to_lowercase())
+            .unwrap_or_else(|| TYPE_PAR_DEFAUT.to_string())
+    }
+
+    fn modele_rappel(&self, type_rdv: &str) -> ModeleRappel {
+        self.modeles_rappel
+            .iter()
+            .find(|m| m.type_rdv == type_rdv)
+            .or_else(|| {
+                self.modeles_rappel
+                    .iter()
+                    .find(|m| m.type_rdv == TYPE_PAR_DEFAUT)
+            })
+            .cloned()
+            .unwrap_or_else(ModeleRappel::par_defaut)
+    }
+
+    fn rediger_rappel(&self, rdv: &RendezVous, jours_avant: i64) -> Option<Message> {
+        let patient = self.patients.iter().find(|p| p.id == rdv.patient_id)?;
+        let medecin = self.personnel.iter().find(|p| p.id == rdv.personnel_id)?;
+        let type_rdv = self.type_rendez_vous(rdv);
+        let modele = self.modele_rappel(&type_rdv);
+
+        let remplir = |texte: &str| {
+            texte
+                .replace("{patient}", &format!("{} {}", patient.prenom, patient.nom))
+                .replace("{medecin}", &format!("{} {}", medecin.prenom, medecin.nom))
+                .replace("{date}", &rdv.date)
+                .replace("{heure}", &rdv.heure)
+                .replace("{type}", &type_rdv)
+        };
+
+        Some(Message {
+            id: format!("rappel-{}-j{}", rdv.id, jours_avant),
+            destinataire: patient.email.clone().unwrap_or_default(),
+            sujet: remplir(&modele.sujet),
+            corps: remplir(&modele.corps),
+        })
+    }
+
+    // Envoie pour chaque rendez-vous le rappel de l'échéance due (voir
+    // `echeance_due`); relancer la commande n'envoie jamais deux fois le même
+    // rappel.
+    pub fn envoyer_rappels(&mut self, notificateur: &mut dyn Notificateur) -> (usize, usize) {
+        let aujourdhui = chrono::Local::now().date_naive();
+        let mut a_envoyer = Vec::new();
+
+        for rdv in self.rendez_vous.iter().filter(|r| {
+            matches!(
+                r.statut,
+                StatutRendezVous::Planifie | StatutRendezVous::Confirme
+            )
+        }) {
+            let date = match parse_date(&rdv.date) {
+                Some(date) => date,
+                None => continue,
+            };
+            let deja_envoye = |jours_avant: i64| {
+                self.rappels_envoyes
+                    .iter()
+                    .any(|r| r.rdv_id == rdv.id && r.jours_avant == jours_avant)
+            };
+            if let Some(jours_avant) = echeance_due(date, aujourdhui, deja_envoye) {
+                a_envoyer.push((rdv.id, jours_avant));
+            }
+        }
+
+        let (mut envoyes, mut echecs) = (0, 0);
+        for (rdv_id, jours_avant) in a_envoyer {
+            let message = match self
+                .rendez_vous
+                .iter()
+                .find(|r| r.id == rdv_id)
+                .and_then(|rdv| self.rediger_rappel(rdv, jours_avant))
+            {
+                Some(message) => message,
+                None => continue,
+            };
+            if notificateur.exige_destinataire() && message.destinataire.is_empty() {
+                println!(
+                    "{}",
+                    format!("RDV N°{}: patient sans adresse e-mail", rdv_id).yellow()
+                );
+                echecs += 1;
+                continue;
+            }
+            match notificateur.envoyer(&message) {
+                Ok(()) => {
+                    self.rappels_envoyes.push(RappelEnvoye {
+                        rdv_id,
+                        jours_avant,
+                        date_envoi: maintenant(),
+                    });
+                    envoyes += 1;
+                }
+                Err(erreur) => {
+                    println!("{}", format!("RDV N°{}: {}", rdv_id, erreur).red());
+                    echecs += 1;
+                }
+            }
+        }
+        (envoyes, echecs)
+    }
+
+    pub fn lancer_rappels(&mut self) {
+        println!("{}", "\n=== ENVOI DES RAPPELS ===".green());
+        println!("1. Boîte d'envoi locale ({}/)", DOSSIER_SPOOL);
+        println!("2. Serveur SMTP");
+
+        let mut notificateur: Box<dyn Notificateur> = match lire_nombre("Choix: ") {
+            1 => Box::new(BoiteEnvoi::new(DOSSIER_SPOOL)),
+            2 => {
+                let serveur = lire_chaine("Serveur (hôte:port): ");
+                let expediteur = lire_chaine("Adresse de l'expéditeur: ");
+                Box::new(Smtp::new(&serveur, &expediteur))
+            }
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        let (envoyes, echecs) = self.envoyer_rappels(notificateur.as_mut());
+        self.save_data();
+        println!(
+            "{}",
+            format!("\n{} rappel(s) envoyé(s), {} échec(s)", envoyes, echecs).green()
+        );
+    }
+
+    pub fn gerer_modeles_rappel(&mut self) {
+        println!("{}", "\n=== MODÈLES DE RAPPEL ===".green());
+        for modele in &self.modeles_rappel {
+            println!("{}", "-".repeat(40));
+            println!("Type: {}", modele.type_rdv);
+            println!("Sujet: {}", modele.sujet);
+            println!("{}", modele.corps);
+        }
+        println!(
+            "\nVariables disponibles: {{patient}}, {{medecin}}, {{date}}, {{heure}}, {{type}}"
+        );
+
+        let type_rdv = lire_chaine(&format!(
+            "Type de rendez-vous à définir (ex. {}, ou Entrée pour revenir): ",
+            TYPE_PAR_DEFAUT
+        ))
+        .to_lowercase();
+        if type_rdv.is_empty() {
+            return;
+        }
+        let sujet = lire_chaine("Sujet: ");
+        let corps = lire_chaine("Message (\\n pour un saut de ligne): ").replace("\\n", "\n");
+
+        let modele = ModeleRappel {
+            type_rdv,
+            sujet,
+            corps,
+        };
+        match self
+            .modeles_rappel
+            .iter_mut()
+            .find(|m| m.type_rdv == modele.type_rdv)
+        {
+            Some(existant) => *existant = modele,
+            None => self.modeles_rappel.push(modele),
+        }
+        self.save_data();
+        println!("{}", "\nModèle enregistré!".green());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Faux serveur SMTP: répond aux commandes et renvoie tout ce qu'il a reçu
+    fn faux_serveur() -> (String, thread::JoinHandle<String>) {
+        let ecoute = TcpListener::bind("127.0.0.1:0").unwrap();
+        let adresse = ecoute.local_addr().unwrap().to_string();
+        let serveur = thread::spawn(move || {
+            let (flux, _) = ecoute.accept().unwrap();
+            let mut lecteur = BufReader::new(flux.try_clone().unwrap());
+            let mut ecrivain = flux;
+            let mut recu = String::new();
+            let mut dans_data = false;
+
+            write!(ecrivain, "220 faux.serveur ESMTP\r\n").unwrap();
+            loop {
+                let mut ligne = String::new();
+                if lecteur.read_line(&mut ligne).unwrap() == 0 {
+                    break;
+                }
+                recu.push_str(&ligne);
+                let reponse = if dans_data {
+                    if ligne != ".\r\n" {
+                        continue;
+                    }
+                    dans_data = false;
+                    "250 OK"
+                } else if ligne.starts_with("EHLO") {
+                    "250-faux.serveur\r\n250 8BITMIME"
+                } else if ligne.starts_with("DATA") {
+                    dans_data = true;
+                    "354 Fin avec <CRLF>.<CRLF>"
+                } else if ligne.starts_with("QUIT") {
+                    write!(ecrivain, "221 Au revoir\r\n").unwrap();
+                    break;
+                } else {
+                    "250 OK"
+                };
+                write!(ecrivain, "{}\r\n", reponse).unwrap();
+            }
+            recu
+        });
+        (adresse, serveur)
+    }
+
+    #[test]
+    fn envoi_smtp_vers_faux_serveur() {
+        let (adresse, serveur) = faux_serveur();
+        let mut smtp = Smtp::new(&adresse, "accueil@hopital.test");
+        let message = Message {
+            id: "rappel-1-j1".to_string(),
+            destinataire: "patient@exemple.test".to_string(),
+            sujet: "Rappel: rendez-vous du 03/02/2025".to_string(),
+            corps: "Bonjour,\n.ligne commençant par un point".to_string(),
+        };
+
+        smtp.envoyer(&message).unwrap();
+        let recu = serveur.join().unwrap();
+
+        assert!(recu.contains("MAIL FROM:<accueil@hopital.test>\r\n"));
+        assert!(recu.contains("RCPT TO:<patient@exemple.test>\r\n"));
+        assert!(recu.contains("Subject: Rappel: rendez-vous du 03/02/2025\r\n"));
+        assert!(recu.contains("\r\n..ligne commençant par un point\r\n"));
+        assert!(recu.ends_with("QUIT\r\n"));
+    }
+
+    #[test]
+    fn sujet_non_ascii_encode_rfc2047() {
+        assert_eq!(encoder_sujet("Rappel"), "Rappel");
+        assert_eq!(encoder_sujet("Été"), "=?UTF-8?B?w4l0w6k=?=");
+        let long = encoder_sujet(&"é".repeat(40));
+        let mots: Vec<&str> = long.split("\r\n ").collect();
+        assert_eq!(mots.len(), 2);
+        assert!(mots
+            .iter()
+            .all(|m| m.len() <= 75 && m.starts_with("=?UTF-8?B?")));
+    }
+
+    #[test]
+    fn rappel_manque_envoye_avant_le_rendez_vous() {
+        let date = NaiveDate::from_ymd_opt(2025, 2, 10).unwrap();
+        let jour = |j: u32| NaiveDate::from_ymd_opt(2025, 2, j).unwrap();
+        let jamais = |_: i64| false;
+
+        assert_eq!(echeance_due(date, jour(7), jamais), None);
+        assert_eq!(echeance_due(date, jour(8), jamais), Some(2));
+        // J-2 manqué: le rappel part quand même la veille, comme J-1
+        assert_eq!(echeance_due(date, jour(9), jamais), Some(1));
+        assert_eq!(echeance_due(date, jour(9), |j| j == 1), None);
+        assert_eq!(echeance_due(date, jour(10), jamais), None);
+    }
+
+    #[test]
+    fn boite_envoi_ecrit_un_fichier_par_message() {
+        let dossier = std::env::temp_dir().join(format!("spool-test-{}", std::process::id()));
+        let mut boite = BoiteEnvoi::new(&dossier);
+        let message = Message {
+            id: "rappel-7-j2".to_string(),
+            destinataire: String::new(),
+            sujet: "Rappel".to_string(),
+            corps: "Corps du message".to_string(),
+        };
+
+        boite.envoyer(&message).unwrap();
+        let contenu = fs::read_to_string(dossier.join("rappel-7-j2.txt")).unwrap();
+        assert!(contenu.contains("Subject: Rappel\n"));
+        assert!(contenu.contains("Corps du message"));
+        fs::remove_dir_all(dossier).unwrap();
+    }
+}
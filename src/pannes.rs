@@ -0,0 +1,465 @@
+use chrono::{Duration, NaiveDateTime};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date_heure, Application, StatutEquipement,
+    FORMAT_DATE_HEURE,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum GraviteIncident {
+    Mineure,
+    Majeure,
+    Critique,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StatutTicket {
+    Ouvert,
+    EnCours,
+    Resolu,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MiseAJourTicket {
+    date: String,
+    auteur: String,
+    texte: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Resolution {
+    date: String,
+    description: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IncidentEquipement {
+    id: u32,
+    service_id: u32,
+    equipement_id: u32,
+    // Copie du modèle au moment de la panne, pour les statistiques de fiabilité
+    modele: String,
+    description: String,
+    gravite: GraviteIncident,
+    signale_le: String,
+    signale_par: u32,
+    assigne_a: Option<String>,
+    statut: StatutTicket,
+    mises_a_jour: Vec<MiseAJourTicket>,
+    resolution: Option<Resolution>,
+}
+
+impl IncidentEquipement {
+    // Durée d'immobilisation, jusqu'à la résolution ou jusqu'à maintenant
+    fn immobilisation(&self) -> Option<Duration> {
+        let debut = parse_date_heure(&self.signale_le)?;
+        let fin = match &self.resolution {
+            Some(resolution) => parse_date_heure(&resolution.date)?,
+            None => chrono::Local::now().naive_local(),
+        };
+        Some(fin - debut)
+    }
+}
+
+fn formater_duree(duree: Duration) -> String {
+    let heures = duree.num_hours();
+    if heures >= 24 {
+        format!("{}j {}h", heures / 24, heures % 24)
+    } else {
+        format!("{}h{:02}", heures, duree.num_minutes() % 60)
+    }
+}
+
+fn moyenne(durees: &[Duration]) -> Option<Duration> {
+    if durees.is_empty() {
+        return None;
+    }
+    let total: i64 = durees.iter().map(|d| d.num_minutes()).sum();
+    Some(Duration::minutes(total / durees.len() as i64))
+}
+
+impl Application {
+    // Ticket non résolu sur un équipement: seule sa clôture le remet en service
+    pub fn ticket_ouvert(&self, service_id: u32, equipement_id: u32) -> Option<u32> {
+        self.incidents_equipement
+            .iter()
+            .find(|i| {
+                i.service_id == service_id
+                    && i.equipement_id == equipement_id
+                    && i.statut != StatutTicket::Resolu
+            })
+            .map(|i| i.id)
+    }
+
+    // Réservations et rendez-vous touchés par la panne, marqués à replanifier
+    fn signaler_impact_panne(&mut self, equipement_id: u32, depuis: NaiveDateTime) {
+        let touchees: Vec<(String, String, Option<u32>)> = self
+            .reservations_a_venir(equipement_id, depuis)
+            .iter()
+            .filter_map(|r| {
+                let (debut, _) = r.creneau()?;
+                Some((
+                    debut.format(FORMAT_DATE_HEURE).to_string(),
+                    r.libelle(),
+                    r.rendez_vous_id(),
+                ))
+            })
+            .collect();
+        if touchees.is_empty() {
+            println!("Aucune réservation à venir n'est touchée.");
+            return;
+        }
+
+        println!("{}", "Réservations à replanifier:".yellow());
+        for (debut, libelle, rdv_id) in touchees {
+            println!("  {} - {}", debut, libelle);
+            if let Some(rdv) =
+                rdv_id.and_then(|id| self.rendez_vous.iter_mut().find(|r| r.id == id))
+            {
+                rdv.a_replanifier = true;
+            }
+        }
+    }
+
+    fn signaler_panne(&mut self) {
+        println!("{}", "\n=== SIGNALEMENT D'UNE PANNE ===".green());
+        let (service_id, equipement_id) = match self.choisir_equipement() {
+            Some(choix) => choix,
+            None => return,
+        };
+        let signale_par = lire_nombre("Votre ID (personnel): ");
+        if !self
+            .personnel
+            .iter()
+            .any(|p| p.id == signale_par && p.actif())
+        {
+            println!("{}", "Membre du personnel non trouvé!".red());
+            return;
+        }
+        let description = lire_chaine("Description de la panne: ");
+
+        // Un seul ticket ouvert par équipement: le nouveau signalement s'y ajoute
+        if let Some(ticket) = self.ticket_ouvert(service_id, equipement_id) {
+            let auteur = self.nom_personnel(signale_par);
+            if let Some(incident) = self
+                .incidents_equipement
+                .iter_mut()
+                .find(|i| i.id == ticket)
+            {
+                incident.mises_a_jour.push(MiseAJourTicket {
+                    date: maintenant(),
+                    auteur,
+                    texte: format!("Nouveau signalement: {}", description),
+                });
+            }
+            self.save_data();
+            println!(
+                "{}",
+                format!(
+                    "\nTicket N°{} déjà ouvert sur cet équipement, signalement ajouté au ticket.",
+                    ticket
+                )
+                .yellow()
+            );
+            return;
+        }
+        println!("1. Mineure");
+        println!("2. Majeure");
+        println!("3. Critique");
+        let gravite = match lire_nombre("Gravité: ") {
+            1 => GraviteIncident::Mineure,
+            2 => GraviteIncident::Majeure,
+            3 => GraviteIncident::Critique,
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        let id = (self.incidents_equipement.len() + 1) as u32;
+        let equipement = match self.equipement_mut(service_id, equipement_id) {
+            Some(equipement) => equipement,
+            None => return,
+        };
+        let modele = if equipement.modele.is_empty() {
+            equipement.nom.clone()
+        } else {
+            equipement.modele.clone()
+        };
+        if equipement.statut != StatutEquipement::HorsService {
+            equipement.changer_statut(
+                StatutEquipement::HorsService,
+                &format!("Panne (ticket N°{})", id),
+            );
+        }
+
+        let signale_le = maintenant();
+        self.incidents_equipement.push(IncidentEquipement {
+            id,
+            service_id,
+            equipement_id,
+            modele,
+            description,
+            gravite,
+            signale_le: signale_le.clone(),
+            signale_par,
+            assigne_a: None,
+            statut: StatutTicket::Ouvert,
+            mises_a_jour: Vec::new(),
+            resolution: None,
+        });
+        println!(
+            "{}",
+            format!(
+                "\nTicket de réparation N°{} ouvert, équipement hors service.",
+                id
+            )
+            .green()
+        );
+        if let Some(depuis) = parse_date_heure(&signale_le) {
+            self.signaler_impact_panne(equipement_id, depuis);
+        }
+        self.save_data();
+    }
+
+    fn liste_tickets(&self) {
+        println!("{}", "\n=== TICKETS DE RÉPARATION ===".green());
+        let tous = lire_chaine("Inclure les tickets résolus (o/n): ").eq_ignore_ascii_case("o");
+        for incident in self
+            .incidents_equipement
+            .iter()
+            .filter(|i| tous || i.statut != StatutTicket::Resolu)
+        {
+            println!("{}", "-".repeat(40));
+            let equipement = self
+                .services
+                .iter()
+                .find(|s| s.id == incident.service_id)
+                .and_then(|s| {
+                    s.equipements
+                        .iter()
+                        .find(|e| e.id == incident.equipement_id)
+                });
+            println!(
+                "Ticket N°{} - {} N°{} ({})",
+                incident.id,
+                equipement.map(|e| e.nom.as_str()).unwrap_or("?"),
+                incident.equipement_id,
+                incident.modele
+            );
+            let gravite = format!("Gravité: {:?}", incident.gravite);
+            match incident.gravite {
+                GraviteIncident::Critique => println!("{}", gravite.red()),
+                GraviteIncident::Majeure => println!("{}", gravite.yellow()),
+                GraviteIncident::Mineure => println!("{}", gravite),
+            }
+            println!(
+                "Signalé le {} par {}",
+                incident.signale_le,
+                self.nom_personnel(incident.signale_par)
+            );
+            println!("{}", incident.description);
+            println!("Statut: {:?}", incident.statut);
+            if let Some(technicien) = &incident.assigne_a {
+                println!("Assigné à: {}", technicien);
+            }
+            for mise_a_jour in &incident.mises_a_jour {
+                println!(
+                    "  {} ({}): {}",
+                    mise_a_jour.date, mise_a_jour.auteur, mise_a_jour.texte
+                );
+            }
+            if let Some(resolution) = &incident.resolution {
+                println!("Résolu le {}: {}", resolution.date, resolution.description);
+            }
+            if let Some(duree) = incident.immobilisation() {
+                println!("Immobilisation: {}", formater_duree(duree));
+            }
+        }
+    }
+
+    fn mettre_a_jour_ticket(&mut self) {
+        println!("{}", "\n=== SUIVI D'UN TICKET ===".green());
+        let id = lire_nombre("ID du ticket: ");
+        let incident = match self
+            .incidents_equipement
+            .iter_mut()
+            .find(|i| i.id == id && i.statut != StatutTicket::Resolu)
+        {
+            Some(incident) => incident,
+            None => {
+                println!("{}", "Ticket ouvert non trouvé!".red());
+                return;
+            }
+        };
+        let assigne = lire_chaine(&format!(
+            "Technicien assigné (Entrée pour {}): ",
+            incident.assigne_a.as_deref().unwrap_or("aucun")
+        ));
+        if !assigne.is_empty() {
+            incident.assigne_a = Some(assigne);
+        }
+        let texte = lire_chaine("Mise à jour: ");
+        if !texte.is_empty() {
+            incident.mises_a_jour.push(MiseAJourTicket {
+                date: maintenant(),
+                auteur: incident.assigne_a.clone().unwrap_or_default(),
+                texte,
+            });
+        }
+        if incident.assigne_a.is_some() {
+            incident.statut = StatutTicket::EnCours;
+        }
+        self.save_data();
+        println!("{}", "\nTicket mis à jour!".green());
+    }
+
+    fn clore_ticket(&mut self) {
+        println!("{}", "\n=== RÉSOLUTION D'UN TICKET ===".green());
+        let id = lire_nombre("ID du ticket: ");
+        let (service_id, equipement_id, technicien) = match self
+            .incidents_equipement
+            .iter()
+            .find(|i| i.id == id && i.statut != StatutTicket::Resolu)
+        {
+            Some(incident) => (
+                incident.service_id,
+                incident.equipement_id,
+                incident.assigne_a.clone().unwrap_or_default(),
+            ),
+            None => {
+                println!("{}", "Ticket ouvert non trouvé!".red());
+                return;
+            }
+        };
+        let description = lire_chaine("Résolution: ");
+        let remise_en_service =
+            lire_chaine("Remettre l'équipement en service (o/n): ").eq_ignore_ascii_case("o");
+
+        if let Some(incident) = self.incidents_equipement.iter_mut().find(|i| i.id == id) {
+            incident.statut = StatutTicket::Resolu;
+            incident.resolution = Some(Resolution {
+                date: maintenant(),
+                description: description.clone(),
+            });
+            if let Some(duree) = incident.immobilisation() {
+                println!("Immobilisation: {}", formater_duree(duree));
+            }
+        }
+        // Un autre ticket encore ouvert garde l'équipement hors service
+        let autre_ticket = self.ticket_ouvert(service_id, equipement_id);
+        if let (true, Some(autre)) = (remise_en_service, autre_ticket) {
+            println!(
+                "{}",
+                format!(
+                    "⚠️ Ticket N°{} encore ouvert: l'équipement ne sera remis en service qu'à sa clôture.",
+                    autre
+                )
+                .yellow()
+            );
+        }
+        if let Some(equipement) = self.equipement_mut(service_id, equipement_id) {
+            equipement.journaliser_reparation(&technicien, &description);
+            if remise_en_service && autre_ticket.is_none() {
+                equipement.changer_statut(
+                    StatutEquipement::Fonctionnel,
+                    &format!("Réparé (ticket N°{})", id),
+                );
+            }
+        }
+        self.save_data();
+        println!("{}", "\nTicket clos!".green());
+    }
+
+    // MTBF: temps moyen de bon fonctionnement entre la remise en service et la
+    // panne suivante d'un même appareil. MTTR: durée moyenne de réparation.
+    fn fiabilite_par_modele(&self) {
+        println!("{}", "\n=== FIABILITÉ PAR MODÈLE ===".green());
+        let mut modeles: Vec<&str> = self
+            .incidents_equipement
+            .iter()
+            .map(|i| i.modele.as_str())
+            .collect();
+        modeles.sort();
+        modeles.dedup();
+        if modeles.is_empty() {
+            println!("Aucune panne enregistrée.");
+        }
+
+        for modele in modeles {
+            let incidents: Vec<&IncidentEquipement> = self
+                .incidents_equipement
+                .iter()
+                .filter(|i| i.modele == modele)
+                .collect();
+            let reparations: Vec<Duration> = incidents
+                .iter()
+                .filter(|i| i.resolution.is_some())
+                .filter_map(|i| i.immobilisation())
+                .collect();
+
+            let mut bon_fonctionnement = Vec::new();
+            let mut appareils: Vec<u32> = incidents.iter().map(|i| i.equipement_id).collect();
+            appareils.sort();
+            appareils.dedup();
+            for appareil in &appareils {
+                let mut pannes: Vec<&&IncidentEquipement> = incidents
+                    .iter()
+                    .filter(|i| i.equipement_id == *appareil)
+                    .collect();
+                pannes.sort_by_key(|i| parse_date_heure(&i.signale_le));
+                for paire in pannes.windows(2) {
+                    let remise = paire[0]
+                        .resolution
+                        .as_ref()
+                        .and_then(|r| parse_date_heure(&r.date));
+                    if let (Some(remise), Some(panne)) =
+                        (remise, parse_date_heure(&paire[1].signale_le))
+                    {
+                        bon_fonctionnement.push(panne - remise);
+                    }
+                }
+            }
+
+            println!("{}", "-".repeat(40));
+            println!(
+                "{}: {} panne(s) sur {} appareil(s)",
+                modele,
+                incidents.len(),
+                appareils.len()
+            );
+            match moyenne(&bon_fonctionnement) {
+                Some(mtbf) => println!("MTBF: {}", formater_duree(mtbf)),
+                None => println!("MTBF: données insuffisantes"),
+            }
+            match moyenne(&reparations) {
+                Some(mttr) => println!("MTTR: {}", formater_duree(mttr)),
+                None => println!("MTTR: aucune réparation terminée"),
+            }
+        }
+    }
+
+    pub fn menu_pannes(&mut self) {
+        loop {
+            println!("\n{}", "=== PANNES ET RÉPARATIONS ===".blue().bold());
+            println!("1. Signaler une panne");
+            println!("2. Tickets de réparation");
+            println!("3. Assigner ou suivre un ticket");
+            println!("4. Clore un ticket");
+            println!("5. Fiabilité par modèle (MTBF/MTTR)");
+            println!("6. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.signaler_panne(),
+                2 => self.liste_tickets(),
+                3 => self.mettre_a_jour_ticket(),
+                4 => self.clore_ticket(),
+                5 => self.fiabilite_par_modele(),
+                6 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
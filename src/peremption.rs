@@ -0,0 +1,363 @@
+use colored::*;
+
+use crate::pharmacie::TypeMouvement;
+use crate::{lire_chaine, lire_nombre, Application};
+
+// Motif posé par la mise en quarantaine groupée des lots périmés
+const MOTIF_PEREMPTION: &str = "Péremption";
+
+pub fn delai_par_defaut() -> u32 {
+    30
+}
+
+impl Application {
+    // Lots en stock (hors quarantaine) périmés ou périmant dans le délai d'alerte
+    fn lots_a_surveiller(&self) -> Vec<(String, String, u32, String, i64)> {
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let delai = self.pharmacie.delai_alerte_peremption as i64;
+        let mut lots: Vec<_> = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .flat_map(|m| m.lots.iter().map(move |l| (m, l)))
+            .filter(|(_, l)| l.quantite() > 0 && !l.en_quarantaine())
+            .filter_map(|(m, l)| {
+                let jours = l.jours_avant_peremption(aujourd_hui)?;
+                (jours <= delai).then(|| {
+                    (
+                        m.nom.clone(),
+                        l.numero().to_string(),
+                        l.quantite(),
+                        l.date_peremption().to_string(),
+                        jours,
+                    )
+                })
+            })
+            .collect();
+        lots.sort_by_key(|(_, _, _, _, jours)| *jours);
+        lots
+    }
+
+    fn afficher_lots_a_surveiller(&self) -> usize {
+        let lots = self.lots_a_surveiller();
+        for (medicament, numero, quantite, date_peremption, jours) in &lots {
+            let ligne = format!(
+                "{} - lot {} : {} unité(s), péremption {}",
+                medicament, numero, quantite, date_peremption
+            );
+            if *jours < 0 {
+                println!("{}", format!("{} (PÉRIMÉ)", ligne).red());
+            } else {
+                println!("{}", format!("{} (dans {} jour(s))", ligne, jours).yellow());
+            }
+        }
+        lots.len()
+    }
+
+    pub fn alertes_peremption(&self) {
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let a_surveiller = self.lots_a_surveiller();
+        if a_surveiller.is_empty() {
+            return;
+        }
+        let perimes = a_surveiller
+            .iter()
+            .filter(|(_, _, _, _, jours)| *jours < 0)
+            .count();
+        println!(
+            "{}",
+            format!(
+                "⚠️ {} lot(s) périmé(s) et {} lot(s) périmant sous {} jours (au {}):",
+                perimes,
+                a_surveiller.len() - perimes,
+                self.pharmacie.delai_alerte_peremption,
+                aujourd_hui.format("%d/%m/%Y")
+            )
+            .yellow()
+        );
+        self.afficher_lots_a_surveiller();
+    }
+
+    fn rapport_peremption(&self) {
+        println!(
+            "{}",
+            "\n=== LOTS PÉRIMÉS OU PROCHES DE LA PÉREMPTION ===".green()
+        );
+        if self.afficher_lots_a_surveiller() == 0 {
+            println!(
+                "Aucun lot ne périme dans les {} prochains jours.",
+                self.pharmacie.delai_alerte_peremption
+            );
+        }
+    }
+
+    fn mettre_lot_en_quarantaine(&mut self) {
+        println!("{}", "\n=== MISE EN QUARANTAINE ===".green());
+        if self.afficher_lots_a_surveiller() == 0 {
+            println!("Aucun lot périmé ou proche de la péremption.");
+        }
+        let medicament_id = lire_nombre("ID du médicament: ");
+        let numero = lire_chaine("Numéro de lot: ");
+        let motif = lire_chaine("Motif: ");
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        let resultat = self
+            .pharmacie
+            .medicaments
+            .iter_mut()
+            .find(|m| m.id == medicament_id)
+            .ok_or("Médicament non trouvé!".to_string())
+            .and_then(|m| m.mettre_en_quarantaine(&numero, utilisateur_id, motif));
+        match resultat {
+            Ok(()) => {
+                self.save_data();
+                println!("{}", "\nLot mis en quarantaine!".green());
+            }
+            Err(erreur) => println!("{}", erreur.red()),
+        }
+    }
+
+    // Tous les lots périmés encore en stock passent en quarantaine d'un coup
+    fn quarantaine_lots_perimes(&mut self) {
+        println!("{}", "\n=== QUARANTAINE DES LOTS PÉRIMÉS ===".green());
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let perimes: Vec<(u32, String)> = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .flat_map(|m| m.lots.iter().map(move |l| (m.id, l)))
+            .filter(|(_, l)| l.quantite() > 0 && !l.en_quarantaine() && l.perime(aujourd_hui))
+            .map(|(id, l)| (id, l.numero().to_string()))
+            .collect();
+        if perimes.is_empty() {
+            println!("Aucun lot périmé hors quarantaine.");
+            return;
+        }
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        let mut isoles = 0;
+        for (medicament_id, numero) in &perimes {
+            if let Some(medicament) = self
+                .pharmacie
+                .medicaments
+                .iter_mut()
+                .find(|m| m.id == *medicament_id)
+            {
+                match medicament.mettre_en_quarantaine(
+                    numero,
+                    utilisateur_id,
+                    MOTIF_PEREMPTION.to_string(),
+                ) {
+                    Ok(()) => isoles += 1,
+                    Err(erreur) => println!("{}", format!("Lot {}: {}", numero, erreur).red()),
+                }
+            }
+        }
+        self.save_data();
+        println!(
+            "{}",
+            format!("\n{} lot(s) mis en quarantaine!", isoles).green()
+        );
+    }
+
+    // La destruction solde le lot par un mouvement signé: péremption pour un lot
+    // périmé ou isolé pour péremption, destruction pour tout autre motif (rappel...)
+    fn detruire_lot(&mut self) {
+        println!(
+            "{}",
+            "\n=== DESTRUCTION D'UN LOT EN QUARANTAINE ===".green()
+        );
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let en_quarantaine: Vec<(u32, String, String, u32, String, bool)> = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .flat_map(|m| m.lots.iter().map(move |l| (m, l)))
+            .filter(|(_, l)| l.quantite() > 0 && l.en_quarantaine())
+            .map(|(m, l)| {
+                let motif = l.motif_quarantaine().unwrap_or_default().to_string();
+                let peremption = l.perime(aujourd_hui) || motif == MOTIF_PEREMPTION;
+                (
+                    m.id,
+                    m.nom.clone(),
+                    l.numero().to_string(),
+                    l.quantite(),
+                    motif,
+                    peremption,
+                )
+            })
+            .collect();
+        if en_quarantaine.is_empty() {
+            println!("Aucun lot en quarantaine.");
+            return;
+        }
+        for (medicament_id, nom, numero, quantite, motif, _) in &en_quarantaine {
+            println!(
+                "Médicament #{} {} - lot {} : {} unité(s) ({})",
+                medicament_id, nom, numero, quantite, motif
+            );
+        }
+        let medicament_id = lire_nombre("ID du médicament: ");
+        let numero = lire_chaine("Numéro de lot: ");
+        let Some((_, _, _, quantite, motif, peremption)) = en_quarantaine
+            .iter()
+            .find(|(id, _, lot, ..)| *id == medicament_id && *lot == numero)
+        else {
+            println!("{}", "Ce lot n'est pas en quarantaine!".red());
+            return;
+        };
+        let mode = lire_chaine("Mode de destruction (incinération, filière DASRI...): ");
+        let resultat = self.identifier_utilisateur().and_then(|utilisateur_id| {
+            self.enregistrer_mouvement(
+                medicament_id,
+                if *peremption {
+                    TypeMouvement::Peremption
+                } else {
+                    TypeMouvement::Destruction
+                },
+                *quantite,
+                Some(&numero),
+                Some(utilisateur_id),
+                format!("Destruction: {} (quarantaine: {})", mode, motif),
+            )
+        });
+        match resultat {
+            Ok(()) => {
+                self.save_data();
+                println!("{}", "\nDestruction enregistrée!".green());
+            }
+            Err(erreur) => println!("{}", erreur.red()),
+        }
+    }
+
+    fn registre_destructions(&self) {
+        println!("{}", "\n=== REGISTRE DES DESTRUCTIONS ===".green());
+        let mut vide = true;
+        for mouvement in self.pharmacie.mouvements.iter().filter(|m| {
+            matches!(
+                m.type_mouvement(),
+                TypeMouvement::Peremption | TypeMouvement::Destruction
+            )
+        }) {
+            vide = false;
+            let medicament = self
+                .pharmacie
+                .medicaments
+                .iter()
+                .find(|m| m.id == mouvement.medicament_id())
+                .map(|m| m.nom.as_str())
+                .unwrap_or("?");
+            let lots: Vec<String> = mouvement
+                .lots()
+                .iter()
+                .map(|(numero, quantite)| format!("lot {} x{}", numero, quantite))
+                .collect();
+            println!("{}", "-".repeat(40));
+            println!(
+                "{} - {} ({}) - {}",
+                mouvement.date(),
+                medicament,
+                lots.join(", "),
+                mouvement.type_mouvement().libelle()
+            );
+            println!("Par: {}", self.nom_utilisateur(mouvement.utilisateur_id()));
+            println!("{}", mouvement.commentaire());
+        }
+        if vide {
+            println!("Aucune destruction enregistrée.");
+        }
+    }
+
+    fn configurer_delai_peremption(&mut self) {
+        println!(
+            "Délai d'alerte actuel: {} jour(s)",
+            self.pharmacie.delai_alerte_peremption
+        );
+        let delai = lire_nombre("Nouveau délai d'alerte (jours): ");
+        if delai == 0 {
+            println!("{}", "Le délai doit être d'au moins un jour!".red());
+            return;
+        }
+        self.pharmacie.delai_alerte_peremption = delai;
+        self.save_data();
+        println!("{}", "\nDélai d'alerte mis à jour!".green());
+    }
+
+    pub fn statistiques_peremption(&self) {
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let delai = self.pharmacie.delai_alerte_peremption as i64;
+        let lots: Vec<_> = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .flat_map(|m| &m.lots)
+            .filter(|l| l.quantite() > 0)
+            .collect();
+        let en_quarantaine = lots.iter().filter(|l| l.en_quarantaine()).count();
+        let perimes = lots
+            .iter()
+            .filter(|l| !l.en_quarantaine() && l.perime(aujourd_hui))
+            .count();
+        let proches = lots
+            .iter()
+            .filter(|l| !l.en_quarantaine())
+            .filter(|l| {
+                l.jours_avant_peremption(aujourd_hui)
+                    .is_some_and(|j| (0..=delai).contains(&j))
+            })
+            .count();
+        let detruits = |type_mouvement: TypeMouvement| -> i64 {
+            self.pharmacie
+                .mouvements
+                .iter()
+                .filter(|m| *m.type_mouvement() == type_mouvement)
+                .map(|m| -m.variation())
+                .sum()
+        };
+        println!("Lots périmés en stock: {}", perimes);
+        println!("Lots périmant sous {} jours: {}", delai, proches);
+        println!("Lots en quarantaine: {}", en_quarantaine);
+        println!(
+            "Unités détruites pour péremption: {}",
+            detruits(TypeMouvement::Peremption)
+        );
+        println!(
+            "Unités détruites pour un autre motif: {}",
+            detruits(TypeMouvement::Destruction)
+        );
+    }
+
+    pub fn menu_peremption(&mut self) {
+        loop {
+            println!("\n{}", "=== PÉREMPTIONS ===".blue().bold());
+            println!("1. Lots périmés ou proches de la péremption");
+            println!("2. Mettre un lot en quarantaine");
+            println!("3. Mettre en quarantaine tous les lots périmés");
+            println!("4. Détruire un lot en quarantaine");
+            println!("5. Registre des destructions");
+            println!("6. Délai d'alerte de péremption");
+            println!("7. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.rapport_peremption(),
+                2 => self.mettre_lot_en_quarantaine(),
+                3 => self.quarantaine_lots_perimes(),
+                4 => self.detruire_lot(),
+                5 => self.registre_destructions(),
+                6 => self.configurer_delai_peremption(),
+                7 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
@@ -0,0 +1,540 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{lire_chaine, lire_nombre, maintenant, parse_date, Application, Role};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum TypeMouvement {
+    Entree,
+    SortieService(u32),
+    Dispensation { patient_id: u32, traitement: usize },
+    Retour,
+    Perte,
+    Peremption,
+    // Destruction d'un lot en quarantaine pour un autre motif que la péremption (rappel...)
+    Destruction,
+    // Écart constaté au rapprochement entre le stock et le journal, ou à l'inventaire d'un service
+    Ajustement,
+    // Sortie de l'armoire d'un service, pour un patient ou non
+    Consommation { patient_id: Option<u32> },
+}
+
+impl TypeMouvement {
+    pub fn libelle(&self) -> String {
+        match self {
+            TypeMouvement::Entree => "Entrée".to_string(),
+            TypeMouvement::SortieService(service_id) => {
+                format!("Sortie vers le service #{}", service_id)
+            }
+            TypeMouvement::Dispensation { patient_id, .. } => {
+                format!("Dispensation au patient #{}", patient_id)
+            }
+            TypeMouvement::Retour => "Retour".to_string(),
+            TypeMouvement::Perte => "Perte".to_string(),
+            TypeMouvement::Peremption => "Péremption".to_string(),
+            TypeMouvement::Destruction => "Destruction".to_string(),
+            TypeMouvement::Ajustement => "Ajustement".to_string(),
+            TypeMouvement::Consommation {
+                patient_id: Some(patient_id),
+            } => format!("Consommation pour le patient #{}", patient_id),
+            TypeMouvement::Consommation { patient_id: None } => "Consommation".to_string(),
+        }
+    }
+
+    fn entrant(&self) -> bool {
+        matches!(self, TypeMouvement::Entree | TypeMouvement::Retour)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MouvementStock {
+    id: u32,
+    medicament_id: u32,
+    date: String,
+    type_mouvement: TypeMouvement,
+    // Positive pour une entrée, négative pour une sortie
+    variation: i64,
+    utilisateur_id: Option<u32>,
+    commentaire: String,
+    // Lots concernés et quantité par lot
+    #[serde(default)]
+    lots: Vec<(String, u32)>,
+    // Armoire du service concerné, ou pharmacie centrale si absent
+    #[serde(default)]
+    emplacement: Option<u32>,
+}
+
+impl MouvementStock {
+    pub fn medicament_id(&self) -> u32 {
+        self.medicament_id
+    }
+
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    pub fn type_mouvement(&self) -> &TypeMouvement {
+        &self.type_mouvement
+    }
+
+    pub fn variation(&self) -> i64 {
+        self.variation
+    }
+
+    pub fn utilisateur_id(&self) -> Option<u32> {
+        self.utilisateur_id
+    }
+
+    pub fn commentaire(&self) -> &str {
+        &self.commentaire
+    }
+
+    pub fn emplacement(&self) -> Option<u32> {
+        self.emplacement
+    }
+
+    pub fn lots(&self) -> &[(String, u32)] {
+        &self.lots
+    }
+
+    pub fn quantite_lot(&self, numero: &str) -> Option<u32> {
+        self.lots
+            .iter()
+            .find(|(lot, _)| lot == numero)
+            .map(|(_, quantite)| *quantite)
+    }
+}
+
+impl Application {
+    // Ajoute une ligne au journal sans toucher au stock du médicament
+    pub fn journaliser_mouvement(
+        &mut self,
+        medicament_id: u32,
+        type_mouvement: TypeMouvement,
+        variation: i64,
+        utilisateur_id: Option<u32>,
+        commentaire: String,
+        lots: Vec<(String, u32)>,
+    ) {
+        let id = (self.pharmacie.mouvements.len() + 1) as u32;
+        self.pharmacie.mouvements.push(MouvementStock {
+            id,
+            medicament_id,
+            date: maintenant(),
+            type_mouvement,
+            variation,
+            utilisateur_id,
+            commentaire,
+            lots,
+            emplacement: None,
+        });
+    }
+
+    // Mouvement de l'armoire d'un service, hors du stock de la pharmacie centrale.
+    // Chaque lot porte sa variation signée; la variation du mouvement en est la somme.
+    pub fn journaliser_mouvement_armoire(
+        &mut self,
+        service_id: u32,
+        medicament_id: u32,
+        type_mouvement: TypeMouvement,
+        lots: Vec<(String, i64)>,
+        utilisateur_id: u32,
+        commentaire: String,
+    ) {
+        let variation = lots.iter().map(|(_, v)| v).sum();
+        self.journaliser_mouvement(
+            medicament_id,
+            type_mouvement,
+            variation,
+            Some(utilisateur_id),
+            commentaire,
+            lots.into_iter()
+                .map(|(numero, v)| (numero, v.unsigned_abs() as u32))
+                .collect(),
+        );
+        if let Some(mouvement) = self.pharmacie.mouvements.last_mut() {
+            mouvement.emplacement = Some(service_id);
+        }
+    }
+
+    pub fn enregistrer_mouvement(
+        &mut self,
+        medicament_id: u32,
+        type_mouvement: TypeMouvement,
+        quantite: u32,
+        lot: Option<&str>,
+        utilisateur_id: Option<u32>,
+        commentaire: String,
+    ) -> Result<(), String> {
+        self.enregistrer_mouvement_lots(
+            medicament_id,
+            type_mouvement,
+            quantite,
+            lot,
+            utilisateur_id,
+            commentaire,
+        )
+        .map(|_| ())
+    }
+
+    // Sans lot précisé, une sortie est servie en FEFO; une entrée exige toujours un lot.
+    // Renvoie les lots mouvementés et la quantité par lot.
+    pub fn enregistrer_mouvement_lots(
+        &mut self,
+        medicament_id: u32,
+        type_mouvement: TypeMouvement,
+        quantite: u32,
+        lot: Option<&str>,
+        utilisateur_id: Option<u32>,
+        commentaire: String,
+    ) -> Result<Vec<(String, u32)>, String> {
+        if quantite == 0 {
+            return Err("La quantité doit être positive!".to_string());
+        }
+        let medicament = self
+            .pharmacie
+            .medicaments
+            .iter_mut()
+            .find(|m| m.id == medicament_id)
+            .ok_or("Médicament non trouvé!")?;
+        let entrant = type_mouvement.entrant();
+        let lots = match (entrant, lot) {
+            (true, Some(numero))
+                if type_mouvement == TypeMouvement::Entree
+                    && medicament.lot(numero).is_some_and(|l| l.en_quarantaine()) =>
+            {
+                return Err(format!(
+                    "Le lot {} est en quarantaine: réception refusée!",
+                    numero
+                ))
+            }
+            (true, Some(numero)) => medicament.crediter_lot(numero, quantite)?,
+            (true, None) => return Err("Numéro de lot obligatoire!".to_string()),
+            (false, None)
+                if matches!(
+                    type_mouvement,
+                    TypeMouvement::Peremption | TypeMouvement::Destruction
+                ) =>
+            {
+                return Err("Numéro de lot obligatoire!".to_string())
+            }
+            (false, Some(numero)) => medicament.prelever_lot(numero, quantite)?,
+            (false, None) => medicament.prelever_fefo(quantite)?,
+        };
+        let variation = if entrant {
+            quantite as i64
+        } else {
+            -(quantite as i64)
+        };
+        self.journaliser_mouvement(
+            medicament_id,
+            type_mouvement,
+            variation,
+            utilisateur_id,
+            commentaire,
+            lots.clone(),
+        );
+        Ok(lots)
+    }
+
+    pub fn entree_initiale(&mut self, medicament_id: u32, quantite: u32) -> Result<(), String> {
+        let lot = self.preparer_lot_entrant(medicament_id, None)?;
+        self.enregistrer_mouvement(
+            medicament_id,
+            TypeMouvement::Entree,
+            quantite,
+            Some(&lot),
+            None,
+            "Stock initial".to_string(),
+        )
+    }
+
+    // Stock tel qu'il découle du journal des mouvements
+    fn stock_journal(&self, medicament_id: u32) -> i64 {
+        self.pharmacie
+            .mouvements
+            .iter()
+            .filter(|m| m.medicament_id == medicament_id && m.emplacement.is_none())
+            .map(|m| m.variation)
+            .sum()
+    }
+
+    // Les mouvements de stock sont signés par un utilisateur authentifié
+    pub fn identifier_utilisateur(&mut self) -> Result<u32, String> {
+        let nom_utilisateur = lire_chaine("Nom d'utilisateur: ");
+        let mot_de_passe = lire_chaine("Mot de passe: ");
+        let utilisateur = self
+            .utilisateurs
+            .iter_mut()
+            .find(|u| u.nom_utilisateur == nom_utilisateur)
+            .filter(|u| bcrypt::verify(&mot_de_passe, &u.mot_de_passe_hash).unwrap_or(false))
+            .ok_or("Identifiants invalides!")?;
+        if matches!(utilisateur.role, Role::Secretaire) {
+            return Err("Rôle non autorisé à effectuer des mouvements de stock!".to_string());
+        }
+        utilisateur.derniere_connexion = Some(maintenant());
+        Ok(utilisateur.id)
+    }
+
+    pub fn nom_utilisateur(&self, utilisateur_id: Option<u32>) -> String {
+        match utilisateur_id {
+            Some(id) => self
+                .utilisateurs
+                .iter()
+                .find(|u| u.id == id)
+                .map(|u| u.nom_utilisateur.clone())
+                .unwrap_or_else(|| format!("Utilisateur #{}", id)),
+            None => "Système".to_string(),
+        }
+    }
+
+    fn saisir_mouvement(&mut self, type_mouvement: TypeMouvement) {
+        self.verifier_stock();
+        let medicament_id = lire_nombre("ID du médicament: ");
+        let lot = match type_mouvement {
+            TypeMouvement::Entree => match self.preparer_lot_entrant(medicament_id, None) {
+                Ok(numero) => Some(numero),
+                Err(erreur) => {
+                    println!("{}", erreur.red());
+                    return;
+                }
+            },
+            _ => Some(lire_chaine(
+                "Numéro de lot (Entrée pour le premier à périmer): ",
+            ))
+            .filter(|numero| !numero.is_empty()),
+        };
+        let quantite = lire_nombre("Quantité: ");
+        let commentaire = lire_chaine("Commentaire: ");
+        let retour = type_mouvement == TypeMouvement::Retour;
+        let resultat = self.identifier_utilisateur().and_then(|utilisateur_id| {
+            self.enregistrer_mouvement(
+                medicament_id,
+                type_mouvement,
+                quantite,
+                lot.as_deref(),
+                Some(utilisateur_id),
+                commentaire,
+            )
+        });
+        match resultat {
+            Ok(()) => {
+                self.save_data();
+                println!("{}", "\nMouvement enregistré!".green());
+                let en_quarantaine = self
+                    .pharmacie
+                    .medicaments
+                    .iter()
+                    .find(|m| m.id == medicament_id)
+                    .zip(lot.as_deref())
+                    .and_then(|(m, numero)| m.lot(numero))
+                    .is_some_and(|l| l.en_quarantaine());
+                if retour && en_quarantaine {
+                    println!(
+                        "{}",
+                        "⚠️ Lot en quarantaine: les unités retournées restent bloquées.".yellow()
+                    );
+                }
+            }
+            Err(erreur) => println!("{}", erreur.red()),
+        }
+    }
+
+    fn dispenser_traitement(&mut self) {
+        println!("{}", "\n=== DISPENSATION À UN PATIENT ===".green());
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+        let traitements = match self.patients.iter().find(|p| p.id == patient_id) {
+            Some(patient) => patient.dossier_medical.traitements.clone(),
+            None => {
+                println!("{}", "Patient non trouvé!".red());
+                return;
+            }
+        };
+        if traitements.is_empty() {
+            println!("{}", "Aucun traitement prescrit à ce patient.".yellow());
+            return;
+        }
+        for (numero, traitement) in traitements.iter().enumerate() {
+            println!(
+                "{}. {} - {} (du {} au {})",
+                numero + 1,
+                traitement.medicament,
+                traitement.posologie,
+                traitement.date_debut,
+                traitement.date_fin.as_deref().unwrap_or("...")
+            );
+        }
+        let numero = lire_nombre("Numéro du traitement: ") as usize;
+        let Some(traitement) = numero.checked_sub(1).and_then(|i| traitements.get(i)) else {
+            println!("{}", "Traitement non trouvé!".red());
+            return;
+        };
+
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let commence = parse_date(&traitement.date_debut).is_some_and(|d| d <= aujourd_hui);
+        let termine = traitement
+            .date_fin
+            .as_deref()
+            .and_then(parse_date)
+            .is_some_and(|d| d < aujourd_hui);
+        if !commence || termine {
+            println!("{}", "Ce traitement n'est pas en cours!".red());
+            return;
+        }
+        let Some(medicament_id) = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .find(|m| m.nom.eq_ignore_ascii_case(&traitement.medicament))
+            .map(|m| m.id)
+        else {
+            println!(
+                "{}",
+                format!(
+                    "{} n'est pas référencé à la pharmacie!",
+                    traitement.medicament
+                )
+                .red()
+            );
+            return;
+        };
+
+        let quantite = lire_nombre("Quantité dispensée: ");
+        let commentaire = format!("{} - {}", traitement.medicament, traitement.posologie);
+        let resultat = self.identifier_utilisateur().and_then(|utilisateur_id| {
+            self.enregistrer_mouvement(
+                medicament_id,
+                TypeMouvement::Dispensation {
+                    patient_id,
+                    traitement: numero - 1,
+                },
+                quantite,
+                None,
+                Some(utilisateur_id),
+                commentaire,
+            )
+        });
+        match resultat {
+            Ok(()) => {
+                self.save_data();
+                println!("{}", "\nDispensation enregistrée!".green());
+            }
+            Err(erreur) => println!("{}", erreur.red()),
+        }
+    }
+
+    fn journal_medicament(&self) {
+        println!("{}", "\n=== JOURNAL DES MOUVEMENTS ===".green());
+        self.verifier_stock();
+        let medicament_id = lire_nombre("ID du médicament: ");
+        let Some(medicament) = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .find(|m| m.id == medicament_id)
+        else {
+            println!("{}", "Médicament non trouvé!".red());
+            return;
+        };
+        println!("\n{} (stock: {})", medicament.nom, medicament.stock);
+        let mut solde = 0;
+        for mouvement in self
+            .pharmacie
+            .mouvements
+            .iter()
+            .filter(|m| m.medicament_id == medicament_id && m.emplacement.is_none())
+        {
+            solde += mouvement.variation;
+            println!("{}", "-".repeat(40));
+            println!(
+                "#{} {} - {} : {:+} (solde {})",
+                mouvement.id,
+                mouvement.date,
+                mouvement.type_mouvement.libelle(),
+                mouvement.variation,
+                solde
+            );
+            println!("Par: {}", self.nom_utilisateur(mouvement.utilisateur_id));
+            if !mouvement.commentaire.is_empty() {
+                println!("{}", mouvement.commentaire);
+            }
+        }
+    }
+
+    // Compare le stock de chaque médicament au solde du journal et régularise les écarts
+    fn rapprochement_stocks(&mut self) {
+        println!("{}", "\n=== RAPPROCHEMENT DES STOCKS ===".green());
+        let ecarts: Vec<(u32, String, i64)> = self
+            .pharmacie
+            .medicaments
+            .iter()
+            .map(|m| {
+                (
+                    m.id,
+                    m.nom.clone(),
+                    m.stock as i64 - self.stock_journal(m.id),
+                )
+            })
+            .filter(|(_, _, ecart)| *ecart != 0)
+            .collect();
+        if ecarts.is_empty() {
+            println!("{}", "Les stocks sont conformes au journal.".green());
+            return;
+        }
+        for (_, nom, ecart) in &ecarts {
+            println!(
+                "{}",
+                format!("{}: écart de {:+} avec le journal", nom, ecart).yellow()
+            );
+        }
+        let reponse = lire_chaine("\nEnregistrer des ajustements pour ces écarts? (o/n): ");
+        if !reponse.eq_ignore_ascii_case("o") {
+            return;
+        }
+        let utilisateur_id = match self.identifier_utilisateur() {
+            Ok(id) => id,
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                return;
+            }
+        };
+        for (medicament_id, _, ecart) in ecarts {
+            self.journaliser_mouvement(
+                medicament_id,
+                TypeMouvement::Ajustement,
+                ecart,
+                Some(utilisateur_id),
+                "Reprise du stock existant".to_string(),
+                Vec::new(),
+            );
+        }
+        self.save_data();
+        println!("{}", "\nAjustements enregistrés!".green());
+    }
+
+    pub fn menu_mouvements_stock(&mut self) {
+        loop {
+            println!("\n{}", "=== MOUVEMENTS DE STOCK ===".blue().bold());
+            // Les sorties vers les services passent par les transferts (Stocks des services)
+            println!("1. Entrée en stock");
+            println!("2. Dispensation à un patient");
+            println!("3. Retour en pharmacie");
+            println!("4. Déclarer une perte");
+            println!("5. Journal d'un médicament");
+            println!("6. Rapprochement stock / journal");
+            println!("7. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.saisir_mouvement(TypeMouvement::Entree),
+                2 => self.dispenser_traitement(),
+                3 => self.saisir_mouvement(TypeMouvement::Retour),
+                4 => self.saisir_mouvement(TypeMouvement::Perte),
+                5 => self.journal_medicament(),
+                6 => self.rapprochement_stocks(),
+                7 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
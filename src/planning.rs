@@ -0,0 +1,318 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use colored::*;
+
+use crate::{lire_chaine, lire_nombre, parse_date, Application, Garde, Horaire, Periode, Planning};
+
+const JOURS: [&str; 7] = [
+    "Lundi", "Mardi", "Mercredi", "Jeudi", "Vendredi", "Samedi", "Dimanche",
+];
+
+pub fn nom_jour(jour: Weekday) -> &'static str {
+    JOURS[jour.num_days_from_monday() as usize]
+}
+
+pub fn parse_heure(valeur: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(valeur, "%H:%M").ok()
+}
+
+impl Periode {
+    pub fn contient(&self, date: NaiveDate) -> bool {
+        match (parse_date(&self.debut), parse_date(&self.fin)) {
+            (Some(debut), Some(fin)) => debut <= date && date <= fin,
+            _ => false,
+        }
+    }
+}
+
+impl Horaire {
+    // Le créneau tient-il entièrement dans cette plage horaire ?
+    fn couvre(&self, debut: NaiveDateTime, fin: NaiveDateTime) -> bool {
+        if self.jour != nom_jour(debut.weekday()) || debut.date() != fin.date() {
+            return false;
+        }
+        match (parse_heure(&self.debut), parse_heure(&self.fin)) {
+            (Some(h_debut), Some(h_fin)) => h_debut <= debut.time() && fin.time() <= h_fin,
+            _ => false,
+        }
+    }
+}
+
+impl Planning {
+    pub fn en_conge(&self, date: NaiveDate) -> bool {
+        self.conges.iter().any(|c| c.contient(date))
+    }
+
+    // Sans horaires saisis, aucune restriction n'est appliquée
+    pub fn travaille(&self, debut: NaiveDateTime, fin: NaiveDateTime) -> bool {
+        self.horaires.is_empty() || self.horaires.iter().any(|h| h.couvre(debut, fin))
+    }
+
+    // Plages horaires du jour, pour la recherche de créneaux
+    pub fn plages(&self, jour: Weekday) -> Vec<(NaiveTime, NaiveTime)> {
+        self.horaires
+            .iter()
+            .filter(|h| h.jour == nom_jour(jour))
+            .filter_map(|h| Some((parse_heure(&h.debut)?, parse_heure(&h.fin)?)))
+            .collect()
+    }
+}
+
+fn choisir_jour() -> Option<&'static str> {
+    for (i, jour) in JOURS.iter().enumerate() {
+        println!("{}. {}", i + 1, jour);
+    }
+    JOURS
+        .get((lire_nombre("Jour: ") as usize).wrapping_sub(1))
+        .copied()
+}
+
+impl Application {
+    pub fn choisir_membre_personnel(&self) -> Option<usize> {
+        self.liste_personnel();
+        let id = lire_nombre("ID du membre du personnel: ");
+        let index = self.personnel.iter().position(|p| p.id == id);
+        if index.is_none() {
+            println!("{}", "Membre du personnel non trouvé!".red());
+        }
+        index
+    }
+
+    fn choisir_nom_service(&self) -> Option<String> {
+        self.liste_services();
+        let id = lire_nombre("ID du service: ");
+        let nom = self
+            .services
+            .iter()
+            .find(|s| s.id == id && s.actif())
+            .map(|s| s.nom.clone());
+        if nom.is_none() {
+            println!("{}", "Service non trouvé ou désactivé!".red());
+        }
+        nom
+    }
+
+    pub fn ajouter_horaire(&mut self) {
+        println!("{}", "\n=== AJOUT D'UN HORAIRE HEBDOMADAIRE ===".green());
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let jour = match choisir_jour() {
+            Some(jour) => jour.to_string(),
+            None => {
+                println!("{}", "Jour invalide!".red());
+                return;
+            }
+        };
+        let debut = lire_chaine("Début (HH:MM): ");
+        let fin = lire_chaine("Fin (HH:MM): ");
+        match (parse_heure(&debut), parse_heure(&fin)) {
+            (Some(d), Some(f)) if d < f => {}
+            _ => {
+                println!("{}", "Horaires invalides!".red());
+                return;
+            }
+        }
+        let service = match self.choisir_nom_service() {
+            Some(service) => service,
+            None => return,
+        };
+
+        self.personnel[index].planning.horaires.push(Horaire {
+            jour,
+            debut,
+            fin,
+            service,
+        });
+        self.save_data();
+        println!("{}", "\nHoraire ajouté avec succès!".green());
+    }
+
+    pub fn ajouter_garde(&mut self) {
+        println!("{}", "\n=== AJOUT D'UNE GARDE ===".green());
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let date = lire_chaine("Date de la garde (JJ/MM/AAAA): ");
+        let jour = match parse_date(&date) {
+            Some(jour) => jour,
+            None => {
+                println!("{}", "Date invalide!".red());
+                return;
+            }
+        };
+        if self.personnel[index].planning.en_conge(jour) {
+            println!(
+                "{}",
+                "Ce membre du personnel est en congé à cette date!".red()
+            );
+            return;
+        }
+        let service = match self.choisir_nom_service() {
+            Some(service) => service,
+            None => return,
+        };
+        let requises = self.qualifications_garde(&service);
+        if let Some(manquante) = self.personnel[index].qualification_manquante(&requises, jour) {
+            println!(
+                "{}",
+                format!("Qualification {} requise pour cette garde!", manquante).red()
+            );
+            return;
+        }
+
+        self.personnel[index]
+            .planning
+            .gardes
+            .push(Garde { date, service });
+        self.save_data();
+        println!("{}", "\nGarde ajoutée avec succès!".green());
+    }
+
+    pub fn saisir_conge(&mut self) {
+        println!("{}", "\n=== SAISIE D'UN CONGÉ ===".green());
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let debut = lire_chaine("Premier jour (JJ/MM/AAAA): ");
+        let fin = lire_chaine("Dernier jour (JJ/MM/AAAA): ");
+        match (parse_date(&debut), parse_date(&fin)) {
+            (Some(d), Some(f)) if d <= f => {}
+            _ => {
+                println!("{}", "Période invalide!".red());
+                return;
+            }
+        }
+
+        self.personnel[index]
+            .planning
+            .conges
+            .push(Periode { debut, fin });
+        self.save_data();
+        println!("{}", "\nCongé enregistré avec succès!".green());
+    }
+
+    pub fn afficher_planning(&self) {
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let membre = &self.personnel[index];
+        println!(
+            "{}",
+            format!("\n=== PLANNING DE {} {} ===", membre.nom, membre.prenom).green()
+        );
+
+        println!("\n--- Horaires hebdomadaires ---");
+        for jour in JOURS {
+            for horaire in membre.planning.horaires.iter().filter(|h| h.jour == jour) {
+                println!(
+                    "{}: {} - {} ({})",
+                    jour, horaire.debut, horaire.fin, horaire.service
+                );
+            }
+        }
+        println!("\n--- Gardes ---");
+        for garde in &membre.planning.gardes {
+            println!("{}: {}", garde.date, garde.service);
+        }
+        println!("\n--- Congés ---");
+        for conge in &membre.planning.conges {
+            println!("Du {} au {}", conge.debut, conge.fin);
+        }
+    }
+
+    pub fn tableau_service_hebdomadaire(&self) {
+        let service = match self.choisir_nom_service() {
+            Some(service) => service,
+            None => return,
+        };
+        let saisie = lire_chaine("Date dans la semaine (JJ/MM/AAAA, Entrée pour cette semaine): ");
+        let reference = if saisie.is_empty() {
+            chrono::Local::now().date_naive()
+        } else {
+            match parse_date(&saisie) {
+                Some(date) => date,
+                None => {
+                    println!("{}", "Date invalide!".red());
+                    return;
+                }
+            }
+        };
+        let lundi = reference - Duration::days(reference.weekday().num_days_from_monday() as i64);
+
+        println!(
+            "{}",
+            format!(
+                "\n=== TABLEAU DE SERVICE: {} - SEMAINE DU {} ===",
+                service,
+                lundi.format("%d/%m/%Y")
+            )
+            .green()
+        );
+        for decalage in 0..7 {
+            let date = lundi + Duration::days(decalage);
+            let date_texte = date.format("%d/%m/%Y").to_string();
+            println!("{}", "-".repeat(40));
+            println!("{} {}", nom_jour(date.weekday()), date_texte);
+
+            for membre in &self.personnel {
+                let en_conge = membre.planning.en_conge(date);
+                for horaire in membre
+                    .planning
+                    .horaires
+                    .iter()
+                    .filter(|h| h.service == service && h.jour == nom_jour(date.weekday()))
+                {
+                    let ligne = format!(
+                        "  {} - {}: {} {}",
+                        horaire.debut, horaire.fin, membre.nom, membre.prenom
+                    );
+                    if en_conge {
+                        println!("{}", format!("{} (en congé)", ligne).yellow());
+                    } else {
+                        println!("{}", ligne);
+                    }
+                }
+                if membre
+                    .planning
+                    .gardes
+                    .iter()
+                    .any(|g| g.service == service && g.date == date_texte)
+                {
+                    println!("  Garde: {} {}", membre.nom, membre.prenom);
+                }
+            }
+        }
+    }
+
+    pub fn menu_planning(&mut self) {
+        loop {
+            println!("\n{}", "=== PLANNING DU PERSONNEL ===".blue().bold());
+            println!("1. Ajouter un horaire hebdomadaire");
+            println!("2. Ajouter une garde");
+            println!("3. Saisir un congé");
+            println!("4. Planning d'un membre du personnel");
+            println!("5. Tableau de service hebdomadaire");
+            println!("6. Demandes de congé");
+            println!("7. Générer le tableau de gardes");
+            println!("8. Contrôle de conformité du temps de travail");
+            println!("9. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.ajouter_horaire(),
+                2 => self.ajouter_garde(),
+                3 => self.saisir_conge(),
+                4 => self.afficher_planning(),
+                5 => self.tableau_service_hebdomadaire(),
+                6 => self.menu_demandes_conge(),
+                7 => self.planifier_gardes(),
+                8 => self.controle_conformite(),
+                9 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
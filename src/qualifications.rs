@@ -0,0 +1,332 @@
+use chrono::{Duration, NaiveDate};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{lire_chaine, lire_nombre, parse_date, Application, Personnel};
+
+// Nombre de jours avant l'échéance à partir duquel une qualification est signalée
+const DELAI_ALERTE_JOURS: i64 = 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Qualification {
+    intitule: String,
+    organisme: String,
+    obtenue_le: String,
+    expire_le: Option<String>,
+}
+
+impl Qualification {
+    pub fn intitule(&self) -> &str {
+        &self.intitule
+    }
+
+    fn expiration(&self) -> Option<NaiveDate> {
+        self.expire_le.as_deref().and_then(parse_date)
+    }
+
+    pub fn valide_le(&self, date: NaiveDate) -> bool {
+        parse_date(&self.obtenue_le).is_some_and(|obtenue| obtenue <= date)
+            && self
+                .expiration()
+                .is_none_or(|expiration| date <= expiration)
+    }
+}
+
+impl Personnel {
+    // Le membre détient-il une qualification valide à cette date ?
+    pub fn qualifie(&self, intitule: &str, date: NaiveDate) -> bool {
+        self.qualifications
+            .iter()
+            .any(|q| q.intitule.eq_ignore_ascii_case(intitule) && q.valide_le(date))
+    }
+
+    // Première qualification de la liste qui manque au membre à cette date
+    pub fn qualification_manquante<'a>(
+        &self,
+        requises: &'a [String],
+        date: NaiveDate,
+    ) -> Option<&'a str> {
+        requises
+            .iter()
+            .find(|q| !self.qualifie(q, date))
+            .map(|q| q.as_str())
+    }
+}
+
+impl Application {
+    // Qualifications exigées pour assurer une garde dans un service
+    pub fn qualifications_garde(&self, nom_service: &str) -> Vec<String> {
+        self.services
+            .iter()
+            .find(|s| s.nom == nom_service)
+            .map(|s| s.qualifications_garde.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn verifier_habilitation(
+        &self,
+        personnel_id: u32,
+        service_id: u32,
+        equipement_id: u32,
+        date: NaiveDate,
+    ) -> Result<(), String> {
+        let membre = self
+            .personnel
+            .iter()
+            .find(|p| p.id == personnel_id)
+            .ok_or("Membre du personnel non trouvé!")?;
+        let equipement = self
+            .services
+            .iter()
+            .find(|s| s.id == service_id)
+            .and_then(|s| s.equipements.iter().find(|e| e.id == equipement_id))
+            .ok_or("Équipement non trouvé!")?;
+        match &equipement.qualification_requise {
+            Some(requise) if !membre.qualifie(requise, date) => Err(format!(
+                "{} {} n'est pas habilité(e) à utiliser {} ({} requis)",
+                membre.nom, membre.prenom, equipement.nom, requise
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn ajouter_qualification(&mut self) {
+        println!("{}", "\n=== AJOUT D'UNE QUALIFICATION ===".green());
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let intitule = lire_chaine("Intitulé (ex: BLS, ACLS, radioprotection): ");
+        if intitule.is_empty() {
+            println!("{}", "Intitulé obligatoire!".red());
+            return;
+        }
+        let organisme = lire_chaine("Organisme de délivrance: ");
+        let obtenue_le = lire_chaine("Date d'obtention (JJ/MM/AAAA): ");
+        let obtention = match parse_date(&obtenue_le) {
+            Some(date) => date,
+            None => {
+                println!("{}", "Date invalide!".red());
+                return;
+            }
+        };
+        let saisie = lire_chaine("Date d'expiration (JJ/MM/AAAA, Entrée si sans limite): ");
+        let expire_le = if saisie.is_empty() {
+            None
+        } else {
+            match parse_date(&saisie) {
+                Some(expiration) if expiration >= obtention => Some(saisie),
+                _ => {
+                    println!("{}", "Date d'expiration invalide!".red());
+                    return;
+                }
+            }
+        };
+
+        // Un renouvellement remplace la qualification de même intitulé
+        let qualifications = &mut self.personnel[index].qualifications;
+        qualifications.retain(|q| !q.intitule.eq_ignore_ascii_case(&intitule));
+        qualifications.push(Qualification {
+            intitule,
+            organisme,
+            obtenue_le,
+            expire_le,
+        });
+        self.save_data();
+        println!("{}", "\nQualification enregistrée!".green());
+    }
+
+    fn afficher_qualifications(&self) {
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let membre = &self.personnel[index];
+        println!(
+            "{}",
+            format!(
+                "\n=== QUALIFICATIONS DE {} {} ===",
+                membre.nom, membre.prenom
+            )
+            .green()
+        );
+        let aujourdhui = chrono::Local::now().date_naive();
+        for qualification in &membre.qualifications {
+            println!("{}", "-".repeat(40));
+            println!("{}", qualification.intitule);
+            println!("Délivrée par: {}", qualification.organisme);
+            println!("Obtenue le: {}", qualification.obtenue_le);
+            match &qualification.expire_le {
+                Some(expire_le) if qualification.valide_le(aujourdhui) => {
+                    println!("Expire le: {}", expire_le)
+                }
+                Some(expire_le) => println!("{}", format!("Expirée le: {}", expire_le).red()),
+                None => println!("Sans date d'expiration"),
+            }
+        }
+    }
+
+    // Qualifications expirées ou arrivant à échéance dans `jours` jours
+    fn qualifications_a_echeance(&self, jours: i64) -> Vec<(&Personnel, &Qualification)> {
+        let aujourdhui = chrono::Local::now().date_naive();
+        let limite = aujourdhui + Duration::days(jours);
+        let mut echeances: Vec<(&Personnel, &Qualification)> = self
+            .personnel
+            .iter()
+            .flat_map(|p| p.qualifications.iter().map(move |q| (p, q)))
+            .filter(|(_, q)| q.expiration().is_some_and(|e| e <= limite))
+            .collect();
+        echeances.sort_by_key(|(_, q)| q.expiration());
+        echeances
+    }
+
+    fn afficher_echeances(&self, jours: i64) {
+        let aujourdhui = chrono::Local::now().date_naive();
+        for (membre, qualification) in self.qualifications_a_echeance(jours) {
+            let expiration = qualification.expiration().unwrap_or(aujourdhui);
+            let ligne = format!(
+                "{} {}: {} ({})",
+                membre.nom,
+                membre.prenom,
+                qualification.intitule,
+                qualification.expire_le.as_deref().unwrap_or_default()
+            );
+            if expiration < aujourdhui {
+                println!("{}", format!("{} - expirée", ligne).red());
+            } else {
+                println!(
+                    "{}",
+                    format!(
+                        "{} - expire dans {} jour(s)",
+                        ligne,
+                        (expiration - aujourdhui).num_days()
+                    )
+                    .yellow()
+                );
+            }
+        }
+    }
+
+    // Rappel affiché au démarrage
+    pub fn alertes_qualifications(&self) {
+        let nombre = self.qualifications_a_echeance(DELAI_ALERTE_JOURS).len();
+        if nombre > 0 {
+            println!(
+                "{}",
+                format!(
+                    "⚠️ {} qualification(s) expirée(s) ou à renouveler sous {} jours:",
+                    nombre, DELAI_ALERTE_JOURS
+                )
+                .yellow()
+            );
+            self.afficher_echeances(DELAI_ALERTE_JOURS);
+        }
+    }
+
+    fn liste_echeances_qualifications(&self) {
+        println!("{}", "\n=== QUALIFICATIONS À RENOUVELER ===".green());
+        let saisie = lire_chaine(&format!(
+            "Horizon en jours (Entrée pour {}): ",
+            DELAI_ALERTE_JOURS
+        ));
+        let jours = saisie.parse().unwrap_or(DELAI_ALERTE_JOURS);
+        if self.qualifications_a_echeance(jours).is_empty() {
+            println!("Aucune qualification à renouveler.");
+        }
+        self.afficher_echeances(jours);
+    }
+
+    fn definir_qualifications_garde(&mut self) {
+        println!(
+            "{}",
+            "\n=== QUALIFICATIONS REQUISES POUR LA GARDE ===".green()
+        );
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        let service = match self.services.iter_mut().find(|s| s.id == service_id) {
+            Some(service) => service,
+            None => {
+                println!("{}", "Service non trouvé!".red());
+                return;
+            }
+        };
+        let saisie =
+            lire_chaine("Qualifications requises (séparées par des virgules, Entrée si aucune): ");
+        service.qualifications_garde = saisie
+            .split(',')
+            .map(|q| q.trim().to_string())
+            .filter(|q| !q.is_empty())
+            .collect();
+        self.save_data();
+        println!("{}", "\nExigences de garde enregistrées!".green());
+    }
+
+    fn definir_habilitation_equipement(&mut self) {
+        println!(
+            "{}",
+            "\n=== HABILITATION REQUISE POUR UN ÉQUIPEMENT ===".green()
+        );
+        let (service_id, equipement_id) = match self.choisir_equipement() {
+            Some(choix) => choix,
+            None => return,
+        };
+        let saisie = lire_chaine("Qualification requise (Entrée si aucune): ");
+        if let Some(equipement) = self
+            .services
+            .iter_mut()
+            .find(|s| s.id == service_id)
+            .and_then(|s| s.equipements.iter_mut().find(|e| e.id == equipement_id))
+        {
+            equipement.qualification_requise = Some(saisie).filter(|q| !q.is_empty());
+        }
+        self.save_data();
+        println!("{}", "\nHabilitation enregistrée!".green());
+    }
+
+    fn personnel_habilite(&self) {
+        println!("{}", "\n=== PERSONNEL HABILITÉ ===".green());
+        let (service_id, equipement_id) = match self.choisir_equipement() {
+            Some(choix) => choix,
+            None => return,
+        };
+        let aujourdhui = chrono::Local::now().date_naive();
+        let habilites: Vec<&Personnel> = self
+            .personnel
+            .iter()
+            .filter(|p| {
+                self.verifier_habilitation(p.id, service_id, equipement_id, aujourdhui)
+                    .is_ok()
+            })
+            .collect();
+        if habilites.is_empty() {
+            println!("Aucun membre du personnel habilité.");
+        }
+        for membre in habilites {
+            println!("{} {} ({})", membre.nom, membre.prenom, membre.specialite);
+        }
+    }
+
+    pub fn menu_qualifications(&mut self) {
+        loop {
+            println!("\n{}", "=== QUALIFICATIONS ===".blue().bold());
+            println!("1. Ajouter ou renouveler une qualification");
+            println!("2. Qualifications d'un membre du personnel");
+            println!("3. Qualifications à renouveler");
+            println!("4. Qualifications requises pour la garde d'un service");
+            println!("5. Habilitation requise pour un équipement");
+            println!("6. Personnel habilité pour un équipement");
+            println!("7. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.ajouter_qualification(),
+                2 => self.afficher_qualifications(),
+                3 => self.liste_echeances_qualifications(),
+                4 => self.definir_qualifications_garde(),
+                5 => self.definir_habilitation_equipement(),
+                6 => self.personnel_habilite(),
+                7 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
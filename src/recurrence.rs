@@ -0,0 +1,391 @@
+use chrono::{Duration, NaiveDate};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::reservations::ObjetReservation;
+use crate::{
+    lire_chaine, lire_nombre, parse_date, parse_date_heure, Application, RendezVous,
+    StatutRendezVous, FORMAT_DATE,
+};
+
+// Garde-fou contre les règles sans fin raisonnable
+const MAX_OCCURRENCES: usize = 366;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum Recurrence {
+    Quotidienne,
+    Hebdomadaire,
+    TousLesNJours(u32),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum FinRecurrence {
+    JusquAu(String),
+    Occurrences(u32),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SerieRendezVous {
+    id: u32,
+    libelle: String,
+    patient_id: u32,
+    personnel_id: u32,
+    premiere_date: String,
+    heure: String,
+    duree: u32,
+    recurrence: Recurrence,
+    fin: FinRecurrence,
+}
+
+impl Recurrence {
+    fn intervalle(&self) -> i64 {
+        match self {
+            Recurrence::Quotidienne => 1,
+            Recurrence::Hebdomadaire => 7,
+            Recurrence::TousLesNJours(n) => (*n).max(1) as i64,
+        }
+    }
+}
+
+impl SerieRendezVous {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn libelle(&self) -> &str {
+        &self.libelle
+    }
+
+    // Dates de toutes les séances prévues par la règle
+    fn dates(&self) -> Vec<NaiveDate> {
+        let premiere = match parse_date(&self.premiere_date) {
+            Some(d) => d,
+            None => return Vec::new(),
+        };
+        let pas = Duration::days(self.recurrence.intervalle());
+
+        let mut dates = Vec::new();
+        let mut date = premiere;
+        loop {
+            let terminee = match &self.fin {
+                FinRecurrence::JusquAu(limite) => {
+                    parse_date(limite).map(|l| date > l).unwrap_or(true)
+                }
+                FinRecurrence::Occurrences(n) => dates.len() >= *n as usize,
+            };
+            if terminee || dates.len() >= MAX_OCCURRENCES {
+                return dates;
+            }
+            dates.push(date);
+            date += pas;
+        }
+    }
+}
+
+fn choisir_recurrence() -> Option<Recurrence> {
+    println!("Répétition:");
+    println!("1. Tous les jours");
+    println!("2. Toutes les semaines");
+    println!("3. Tous les N jours");
+    match lire_nombre("Choix: ") {
+        1 => Some(Recurrence::Quotidienne),
+        2 => Some(Recurrence::Hebdomadaire),
+        3 => match lire_nombre("Nombre de jours entre deux séances: ") {
+            0 => None,
+            n => Some(Recurrence::TousLesNJours(n)),
+        },
+        _ => None,
+    }
+}
+
+fn choisir_fin() -> Option<FinRecurrence> {
+    println!("Fin de la série:");
+    println!("1. Jusqu'à une date");
+    println!("2. Après un nombre de séances");
+    match lire_nombre("Choix: ") {
+        1 => {
+            let date = lire_chaine("Date de fin (JJ/MM/AAAA): ");
+            parse_date(&date).map(|_| FinRecurrence::JusquAu(date))
+        }
+        2 => Some(FinRecurrence::Occurrences(lire_nombre(
+            "Nombre de séances: ",
+        ))),
+        _ => None,
+    }
+}
+
+impl Application {
+    pub fn creer_serie_rendez_vous(&mut self) {
+        println!("{}", "\n=== NOUVELLE SÉRIE DE RENDEZ-VOUS ===".green());
+
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+        if !self.patients.iter().any(|p| p.id == patient_id) {
+            println!("{}", "Patient non trouvé!".red());
+            return;
+        }
+
+        self.liste_personnel();
+        let personnel_id = lire_nombre("ID du médecin: ");
+        if !self.personnel.iter().any(|p| p.id == personnel_id) {
+            println!("{}", "Médecin non trouvé!".red());
+            return;
+        }
+
+        let libelle = lire_chaine("Libellé (kinésithérapie, dialyse...): ");
+        let premiere_date = lire_chaine("Date de la première séance (JJ/MM/AAAA): ");
+        let heure = lire_chaine("Heure (HH:MM): ");
+        let duree = lire_nombre("Durée (minutes): ");
+        if parse_date_heure(&format!("{} {}", premiere_date, heure)).is_none() {
+            println!("{}", "Date ou heure invalide!".red());
+            return;
+        }
+
+        let recurrence = match choisir_recurrence() {
+            Some(r) => r,
+            None => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+        let fin = match choisir_fin() {
+            Some(f) => f,
+            None => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        let serie = SerieRendezVous {
+            id: (self.series.len() + 1) as u32,
+            libelle,
+            patient_id,
+            personnel_id,
+            premiere_date,
+            heure,
+            duree,
+            recurrence,
+            fin,
+        };
+
+        let dates = serie.dates();
+        if dates.is_empty() {
+            println!("{}", "Aucune séance générée par cette règle!".red());
+            return;
+        }
+
+        // Contrôle des conflits séance par séance
+        let mut libres = Vec::new();
+        for date in &dates {
+            let debut = parse_date_heure(&format!("{} {}", date.format(FORMAT_DATE), serie.heure));
+            let verification = match debut {
+                Some(debut) => self.verifier_creneau(debut, duree, patient_id, personnel_id, None),
+                None => Err("Date invalide".to_string()),
+            };
+            match verification {
+                Ok(()) => libres.push(*date),
+                Err(erreur) => println!(
+                    "{}",
+                    format!("{}: {}", date.format(FORMAT_DATE), erreur).red()
+                ),
+            }
+        }
+
+        println!(
+            "\n{} séance(s) prévue(s), {} sans conflit.",
+            dates.len(),
+            libres.len()
+        );
+        if libres.is_empty() {
+            return;
+        }
+        if libres.len() < dates.len()
+            && lire_chaine("Créer uniquement les séances sans conflit ? (O/N) ").to_uppercase()
+                != "O"
+        {
+            println!("{}", "Série abandonnée.".yellow());
+            return;
+        }
+
+        for date in libres {
+            let rdv = RendezVous {
+                id: (self.rendez_vous.len() + 1) as u32,
+                date: date.format(FORMAT_DATE).to_string(),
+                heure: serie.heure.clone(),
+                patient_id,
+                personnel_id,
+                duree,
+                statut: StatutRendezVous::Planifie,
+                historique: Vec::new(),
+                remplace: None,
+                serie_id: Some(serie.id),
+                a_replanifier: false,
+            };
+            self.rendez_vous.push(rdv);
+        }
+        self.series.push(serie);
+        self.save_data();
+        println!("{}", "\nSérie de rendez-vous créée avec succès!".green());
+    }
+
+    fn liste_series(&self) {
+        println!("{}", "\n=== SÉRIES DE RENDEZ-VOUS ===".green());
+        for serie in &self.series {
+            println!("{}", "-".repeat(40));
+            println!("Série N°{} - {}", serie.id, serie.libelle);
+            if let Some(patient) = self.patients.iter().find(|p| p.id == serie.patient_id) {
+                println!("Patient: {} {}", patient.nom, patient.prenom);
+            }
+            println!(
+                "Début: {} à {} ({:?}, fin: {:?})",
+                serie.premiere_date, serie.heure, serie.recurrence, serie.fin
+            );
+        }
+    }
+
+    // Séances encore modifiables de la série, à partir d'une séance donnée
+    fn seances_suivantes(&self, serie_id: u32, a_partir_de: u32) -> Vec<u32> {
+        let depart = match self.rendez_vous.iter().find(|r| r.id == a_partir_de) {
+            Some(rdv) => rdv.debut(),
+            None => return Vec::new(),
+        };
+        self.rendez_vous
+            .iter()
+            .filter(|r| {
+                r.serie_id == Some(serie_id)
+                    && r.debut() >= depart
+                    && r.statut.transitions().contains(&StatutRendezVous::Annule)
+            })
+            .map(|r| r.id)
+            .collect()
+    }
+
+    fn annuler_seances(&mut self, ids: &[u32], motif: &str) {
+        for rdv in self.rendez_vous.iter_mut().filter(|r| ids.contains(&r.id)) {
+            if let Err(erreur) = rdv.changer_statut(StatutRendezVous::Annule, motif.to_string()) {
+                println!("{}", erreur.red());
+            }
+        }
+        println!("{} séance(s) annulée(s).", ids.len());
+        for id in ids {
+            self.liberer_reservations_rdv(*id);
+            self.offrir_creneau_libere(*id);
+        }
+    }
+
+    // Change l'heure de plusieurs séances, seulement si aucune n'entre en conflit
+    fn modifier_heure_seances(&mut self, ids: &[u32], heure: &str) {
+        for id in ids {
+            let rdv = match self.rendez_vous.iter().find(|r| r.id == *id) {
+                Some(rdv) => rdv,
+                None => continue,
+            };
+            let verification = match parse_date_heure(&format!("{} {}", rdv.date, heure)) {
+                Some(debut) => self.verifier_creneau(
+                    debut,
+                    rdv.duree,
+                    rdv.patient_id,
+                    rdv.personnel_id,
+                    Some(rdv.id),
+                ),
+                None => Err("Heure invalide".to_string()),
+            };
+            if let Err(erreur) = verification {
+                println!("{}", format!("Séance du {}: {}", rdv.date, erreur).red());
+                println!("{}", "Aucune séance modifiée.".red());
+                return;
+            }
+        }
+
+        // Les équipements suivent la nouvelle heure; au moindre échec, séances et
+        // réservations reviennent à leur état initial
+        let reservations = self.reservations_equipement.clone();
+        let heures: Vec<(u32, String)> = self
+            .rendez_vous
+            .iter()
+            .filter(|r| ids.contains(&r.id))
+            .map(|r| (r.id, r.heure.clone()))
+            .collect();
+        for id in ids {
+            let rdv = match self.rendez_vous.iter_mut().find(|r| r.id == *id) {
+                Some(rdv) => rdv,
+                None => continue,
+            };
+            rdv.heure = heure.to_string();
+            let date = rdv.date.clone();
+            let resultat = match rdv.debut() {
+                Some(debut) => {
+                    self.deplacer_reservations(&ObjetReservation::RendezVous(*id), debut, None)
+                }
+                None => Err("Heure invalide".to_string()),
+            };
+            if let Err(erreur) = resultat {
+                self.reservations_equipement = reservations;
+                for rdv in self.rendez_vous.iter_mut() {
+                    if let Some((_, ancienne)) = heures.iter().find(|(id, _)| *id == rdv.id) {
+                        rdv.heure = ancienne.clone();
+                    }
+                }
+                println!("{}", format!("Séance du {}: {}", date, erreur).red());
+                println!("{}", "Aucune séance modifiée.".red());
+                return;
+            }
+        }
+        println!("{} séance(s) modifiée(s).", ids.len());
+    }
+
+    pub fn modifier_serie_rendez_vous(&mut self) {
+        self.liste_series();
+        let serie_id = lire_nombre("ID de la série: ");
+        if !self.series.iter().any(|s| s.id == serie_id) {
+            println!("{}", "Série non trouvée!".red());
+            return;
+        }
+
+        println!("\nSéances à venir:");
+        for rdv in self
+            .rendez_vous
+            .iter()
+            .filter(|r| r.serie_id == Some(serie_id) && r.actif())
+        {
+            println!(
+                "  RDV N°{}: {} à {} ({:?})",
+                rdv.id, rdv.date, rdv.heure, rdv.statut
+            );
+        }
+        let rdv_id = lire_nombre("ID de la séance concernée: ");
+        let seances = self.seances_suivantes(serie_id, rdv_id);
+        if !seances.contains(&rdv_id) {
+            println!("{}", "Séance non modifiable ou hors de la série!".red());
+            return;
+        }
+
+        println!("1. Annuler cette séance");
+        println!("2. Annuler cette séance et les suivantes");
+        println!("3. Déplacer cette séance");
+        println!("4. Changer l'heure de cette séance et des suivantes");
+        match lire_nombre("Choix: ") {
+            1 => {
+                let motif = lire_chaine("Motif: ");
+                self.annuler_seances(&[rdv_id], &motif);
+            }
+            2 => {
+                let motif = lire_chaine("Motif: ");
+                self.annuler_seances(&seances, &motif);
+            }
+            3 => {
+                self.deplacer_interactif(rdv_id);
+                return;
+            }
+            4 => {
+                let heure = lire_chaine("Nouvelle heure (HH:MM): ");
+                self.modifier_heure_seances(&seances, &heure);
+            }
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        }
+        self.save_data();
+    }
+}
@@ -0,0 +1,516 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+use colored::*;
+
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date_heure, Application, ChangementStatut, Facture,
+    Prestation, RendezVous, StatutFacture, StatutRendezVous,
+};
+
+// Plage horaire de consultation utilisée par la recherche de créneaux
+pub const HEURE_OUVERTURE: u32 = 8;
+pub const HEURE_FERMETURE: u32 = 18;
+const PAS_CRENEAU: i64 = 15;
+const HORIZON_RECHERCHE: i64 = 30;
+
+// Tarif appliqué aux brouillons de facture générés en fin de consultation
+const TARIF_CONSULTATION: f64 = 30.0;
+const CODE_ACTE_CONSULTATION: &str = "G";
+
+pub fn duree_par_defaut() -> u32 {
+    30
+}
+
+impl StatutRendezVous {
+    pub fn transitions(&self) -> &'static [StatutRendezVous] {
+        use StatutRendezVous::*;
+        match self {
+            Planifie => &[Confirme, Arrive, Annule, Absent],
+            Confirme => &[Arrive, Annule, Absent],
+            Arrive => &[Termine],
+            Termine | Annule | Absent => &[],
+        }
+    }
+
+    fn libelle(&self) -> &'static str {
+        match self {
+            StatutRendezVous::Planifie => "Planifié",
+            StatutRendezVous::Confirme => "Confirmé",
+            StatutRendezVous::Arrive => "Arrivé",
+            StatutRendezVous::Termine => "Terminé",
+            StatutRendezVous::Annule => "Annulé",
+            StatutRendezVous::Absent => "Absent",
+        }
+    }
+}
+
+impl RendezVous {
+    // Un rendez-vous annulé ou manqué ne bloque plus le créneau
+    pub fn actif(&self) -> bool {
+        !matches!(
+            self.statut,
+            StatutRendezVous::Annule | StatutRendezVous::Absent
+        )
+    }
+
+    pub fn changer_statut(
+        &mut self,
+        nouveau: StatutRendezVous,
+        motif: String,
+    ) -> Result<(), String> {
+        if !self.statut.transitions().contains(&nouveau) {
+            return Err(format!(
+                "Transition impossible: {} -> {}",
+                self.statut.libelle(),
+                nouveau.libelle()
+            ));
+        }
+        self.historique.push(ChangementStatut {
+            date: maintenant(),
+            ancien: self.statut,
+            nouveau,
+            motif,
+        });
+        self.statut = nouveau;
+        Ok(())
+    }
+
+    pub fn debut(&self) -> Option<NaiveDateTime> {
+        parse_date_heure(&format!("{} {}", self.date, self.heure))
+    }
+
+    pub fn fin(&self) -> Option<NaiveDateTime> {
+        self.debut()
+            .map(|d| d + Duration::minutes(self.duree as i64))
+    }
+
+    pub fn chevauche(&self, debut: NaiveDateTime, fin: NaiveDateTime) -> bool {
+        if !self.actif() {
+            return false;
+        }
+        match (self.debut(), self.fin()) {
+            (Some(d), Some(f)) => d < fin && debut < f,
+            _ => false,
+        }
+    }
+}
+
+impl Application {
+    // Vérifie qu'un créneau est libre pour le patient et le médecin.
+    // `exclure` permet d'ignorer un rendez-vous existant (déplacement).
+    pub fn verifier_creneau(
+        &self,
+        debut: NaiveDateTime,
+        duree: u32,
+        patient_id: u32,
+        personnel_id: u32,
+        exclure: Option<u32>,
+    ) -> Result<(), String> {
+        if !self.patients.iter().any(|p| p.id == patient_id) {
+            return Err(format!("Patient {} inconnu", patient_id));
+        }
+        let membre = match self.personnel.iter().find(|p| p.id == personnel_id) {
+            Some(membre) => membre,
+            None => return Err(format!("Membre du personnel {} inconnu", personnel_id)),
+        };
+        if !membre.actif_le(debut.date()) {
+            return Err(format!(
+                "{} {} a quitté l'établissement",
+                membre.nom, membre.prenom
+            ));
+        }
+        if !membre.disponible_le(debut.date()) {
+            return Err(format!(
+                "{} {} est {} le {}",
+                membre.nom,
+                membre.prenom,
+                membre.statut_le(debut.date()).libelle().to_lowercase(),
+                debut.format("%d/%m/%Y")
+            ));
+        }
+        if duree == 0 {
+            return Err("La durée doit être positive".to_string());
+        }
+
+        let fin = debut + Duration::minutes(duree as i64);
+        if membre.planning.en_conge(debut.date()) {
+            return Err(format!(
+                "Le médecin est en congé le {}",
+                debut.format("%d/%m/%Y")
+            ));
+        }
+        if !membre.planning.travaille(debut, fin) {
+            return Err("Le créneau est en dehors des horaires de travail du médecin".to_string());
+        }
+        if let Some(indisponibilite) = self
+            .indisponibilites
+            .iter()
+            .find(|i| i.chevauche(personnel_id, debut, fin))
+        {
+            return Err(format!(
+                "Le médecin est indisponible {}",
+                indisponibilite.description()
+            ));
+        }
+        for intervention in self.interventions_chevauchant(debut, fin) {
+            if intervention.mobilise(personnel_id) {
+                return Err("Le médecin est au bloc opératoire sur ce créneau".to_string());
+            }
+            if intervention.patient_id() == patient_id {
+                return Err("Le patient est au bloc opératoire sur ce créneau".to_string());
+            }
+        }
+        for rdv in self
+            .rendez_vous
+            .iter()
+            .filter(|r| Some(r.id) != exclure && r.chevauche(debut, fin))
+        {
+            if rdv.personnel_id == personnel_id {
+                return Err(format!(
+                    "Le médecin a déjà le rendez-vous N°{} le {} à {}",
+                    rdv.id, rdv.date, rdv.heure
+                ));
+            }
+            if rdv.patient_id == patient_id {
+                return Err(format!(
+                    "Le patient a déjà le rendez-vous N°{} le {} à {}",
+                    rdv.id, rdv.date, rdv.heure
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn medecin_disponible(&self, personnel_id: u32, debut: NaiveDateTime, duree: u32) -> bool {
+        let fin = debut + Duration::minutes(duree as i64);
+        !self
+            .rendez_vous
+            .iter()
+            .any(|r| r.personnel_id == personnel_id && r.chevauche(debut, fin))
+            && !self
+                .indisponibilites
+                .iter()
+                .any(|i| i.chevauche(personnel_id, debut, fin))
+            && !self
+                .interventions_chevauchant(debut, fin)
+                .any(|i| i.mobilise(personnel_id))
+    }
+
+    // Prochains créneaux libres d'un médecin, dans ses horaires de travail
+    // ou, à défaut, en jours ouvrés et heures d'ouverture
+    pub fn creneaux_libres(
+        &self,
+        personnel_id: u32,
+        duree: u32,
+        a_partir_de: NaiveDateTime,
+        nombre: usize,
+    ) -> Vec<NaiveDateTime> {
+        let membre = match self.personnel.iter().find(|p| p.id == personnel_id) {
+            Some(membre) => membre,
+            None => return Vec::new(),
+        };
+        let planning = &membre.planning;
+        let mut creneaux = Vec::new();
+        let date_debut = a_partir_de.date();
+        let ouverture = (
+            NaiveTime::from_hms_opt(HEURE_OUVERTURE, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(HEURE_FERMETURE, 0, 0).unwrap(),
+        );
+
+        for jour in 0..HORIZON_RECHERCHE {
+            let date = date_debut + Duration::days(jour);
+            if planning.en_conge(date) || !membre.disponible_le(date) {
+                continue;
+            }
+            let plages = if !planning.horaires.is_empty() {
+                planning.plages(date.weekday())
+            } else if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+                Vec::new()
+            } else {
+                vec![ouverture]
+            };
+
+            for (heure_debut, heure_fin) in plages {
+                let mut debut = date.and_time(heure_debut);
+                let fermeture = date.and_time(heure_fin);
+
+                while debut + Duration::minutes(duree as i64) <= fermeture {
+                    if debut >= a_partir_de && self.medecin_disponible(personnel_id, debut, duree) {
+                        creneaux.push(debut);
+                        if creneaux.len() >= nombre {
+                            return creneaux;
+                        }
+                    }
+                    debut += Duration::minutes(PAS_CRENEAU);
+                }
+            }
+        }
+        creneaux
+    }
+
+    pub fn proposer_creneaux(&self, personnel_id: u32, duree: u32) {
+        let maintenant = chrono::Local::now().naive_local();
+        let creneaux = self.creneaux_libres(personnel_id, duree, maintenant, 5);
+        if creneaux.is_empty() {
+            println!(
+                "Aucun créneau libre dans les {} prochains jours.",
+                HORIZON_RECHERCHE
+            );
+        }
+        for creneau in creneaux {
+            println!("  {}", creneau.format("%d/%m/%Y à %H:%M"));
+        }
+    }
+
+    pub fn rechercher_creneau(&self) {
+        println!("{}", "\n=== RECHERCHE DE CRÉNEAUX LIBRES ===".green());
+        println!("1. Pour un médecin");
+        println!("2. Pour une spécialité");
+        let choix = lire_nombre("Choix: ");
+        let duree = lire_nombre("Durée souhaitée (minutes): ");
+        let maintenant = chrono::Local::now().naive_local();
+
+        match choix {
+            1 => {
+                self.liste_personnel();
+                let personnel_id = lire_nombre("ID du médecin: ");
+                if !self.personnel.iter().any(|p| p.id == personnel_id) {
+                    println!("{}", "Médecin non trouvé!".red());
+                    return;
+                }
+                println!("\nProchains créneaux libres:");
+                self.proposer_creneaux(personnel_id, duree);
+            }
+            2 => {
+                let specialite = lire_chaine("Spécialité: ").to_lowercase();
+                let mut propositions: Vec<(NaiveDateTime, u32)> = self
+                    .personnel
+                    .iter()
+                    .filter(|p| p.actif() && p.specialite.to_lowercase() == specialite)
+                    .flat_map(|p| {
+                        self.creneaux_libres(p.id, duree, maintenant, 5)
+                            .into_iter()
+                            .map(move |c| (c, p.id))
+                    })
+                    .collect();
+                if propositions.is_empty() {
+                    println!("{}", "Aucun créneau trouvé pour cette spécialité.".yellow());
+                    return;
+                }
+                propositions.sort();
+                println!("\nProchains créneaux libres:");
+                for (creneau, personnel_id) in propositions.into_iter().take(5) {
+                    if let Some(medecin) = self.personnel.iter().find(|p| p.id == personnel_id) {
+                        println!(
+                            "  {} - Dr. {} {}",
+                            creneau.format("%d/%m/%Y à %H:%M"),
+                            medecin.nom,
+                            medecin.prenom
+                        );
+                    }
+                }
+            }
+            _ => println!("{}", "Choix invalide!".red()),
+        }
+    }
+
+    pub fn absences_patient(&self, patient_id: u32) -> usize {
+        self.rendez_vous
+            .iter()
+            .filter(|r| r.patient_id == patient_id && r.statut == StatutRendezVous::Absent)
+            .count()
+    }
+
+    // Brouillon de facture créé automatiquement à la fin d'une consultation
+    fn creer_brouillon_facture(&mut self, rdv_id: u32) {
+        if self
+            .factures
+            .iter()
+            .any(|f| f.rendez_vous_id == Some(rdv_id))
+        {
+            return;
+        }
+        let rdv = match self.rendez_vous.iter().find(|r| r.id == rdv_id) {
+            Some(rdv) => rdv,
+            None => return,
+        };
+        let description = match self.personnel.iter().find(|p| p.id == rdv.personnel_id) {
+            Some(medecin) => format!(
+                "Consultation du {} - Dr. {} {}",
+                rdv.date, medecin.nom, medecin.prenom
+            ),
+            None => format!("Consultation du {}", rdv.date),
+        };
+
+        let facture = Facture {
+            id: (self.factures.len() + 1) as u32,
+            patient_id: rdv.patient_id,
+            prestations: vec![Prestation {
+                description,
+                montant: TARIF_CONSULTATION,
+                code_acte: CODE_ACTE_CONSULTATION.to_string(),
+            }],
+            total: TARIF_CONSULTATION,
+            date_emission: chrono::Local::now().format("%d/%m/%Y").to_string(),
+            statut: StatutFacture::Brouillon,
+            rendez_vous_id: Some(rdv_id),
+        };
+        println!("Brouillon de facture N°{} créé.", facture.id);
+        self.factures.push(facture);
+    }
+
+    pub fn changer_statut_rendez_vous(&mut self) {
+        println!("{}", "\n=== STATUT D'UN RENDEZ-VOUS ===".green());
+        self.liste_rendez_vous();
+        let rdv_id = lire_nombre("ID du rendez-vous: ");
+
+        let transitions = match self.rendez_vous.iter().find(|r| r.id == rdv_id) {
+            Some(rdv) => rdv.statut.transitions(),
+            None => {
+                println!("{}", "Rendez-vous non trouvé!".red());
+                return;
+            }
+        };
+        if transitions.is_empty() {
+            println!("{}", "Ce rendez-vous est clôturé.".yellow());
+            return;
+        }
+
+        println!("Nouveau statut:");
+        for (i, statut) in transitions.iter().enumerate() {
+            println!("{}. {}", i + 1, statut.libelle());
+        }
+        let nouveau = match transitions.get((lire_nombre("Choix: ") as usize).wrapping_sub(1)) {
+            Some(statut) => *statut,
+            None => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        let motif = if matches!(nouveau, StatutRendezVous::Annule | StatutRendezVous::Absent) {
+            lire_chaine("Motif: ")
+        } else {
+            String::new()
+        };
+
+        if let Some(rdv) = self.rendez_vous.iter_mut().find(|r| r.id == rdv_id) {
+            if let Err(erreur) = rdv.changer_statut(nouveau, motif) {
+                println!("{}", erreur.red());
+                return;
+            }
+        }
+        match nouveau {
+            StatutRendezVous::Termine => self.creer_brouillon_facture(rdv_id),
+            StatutRendezVous::Annule => {
+                self.liberer_reservations_rdv(rdv_id);
+                self.offrir_creneau_libere(rdv_id);
+            }
+            _ => {}
+        }
+        self.save_data();
+        println!("{}", "\nStatut du rendez-vous modifié avec succès!".green());
+    }
+
+    // Crée un nouveau rendez-vous lié à l'original, qui est annulé
+    pub fn deplacer(
+        &mut self,
+        rdv_id: u32,
+        date: String,
+        heure: String,
+        motif: &str,
+    ) -> Result<u32, String> {
+        let ancien = match self.rendez_vous.iter().find(|r| r.id == rdv_id) {
+            Some(rdv) if rdv.statut.transitions().contains(&StatutRendezVous::Annule) => {
+                rdv.clone()
+            }
+            Some(_) => return Err("Ce rendez-vous ne peut plus être déplacé".to_string()),
+            None => return Err("Rendez-vous non trouvé".to_string()),
+        };
+        let debut = parse_date_heure(&format!("{} {}", date, heure))
+            .ok_or_else(|| "Date ou heure invalide".to_string())?;
+        self.verifier_creneau(
+            debut,
+            ancien.duree,
+            ancien.patient_id,
+            ancien.personnel_id,
+            Some(rdv_id),
+        )?;
+
+        let nouveau_id = (self.rendez_vous.len() + 1) as u32;
+        if let Some(rdv) = self.rendez_vous.iter_mut().find(|r| r.id == rdv_id) {
+            let motif = format!("Déplacé (RDV N°{}): {}", nouveau_id, motif);
+            rdv.changer_statut(StatutRendezVous::Annule, motif)?;
+        }
+        self.rendez_vous.push(RendezVous {
+            id: nouveau_id,
+            date,
+            heure,
+            patient_id: ancien.patient_id,
+            personnel_id: ancien.personnel_id,
+            duree: ancien.duree,
+            statut: StatutRendezVous::Planifie,
+            historique: Vec::new(),
+            remplace: Some(rdv_id),
+            serie_id: ancien.serie_id,
+            a_replanifier: false,
+        });
+        self.reporter_reservations(rdv_id, nouveau_id);
+        self.offrir_creneau_libere(rdv_id);
+        Ok(nouveau_id)
+    }
+
+    pub fn deplacer_rendez_vous(&mut self) {
+        println!("{}", "\n=== DÉPLACEMENT D'UN RENDEZ-VOUS ===".green());
+        self.liste_rendez_vous();
+        let rdv_id = lire_nombre("ID du rendez-vous à déplacer: ");
+        self.deplacer_interactif(rdv_id);
+    }
+
+    pub fn deplacer_interactif(&mut self, rdv_id: u32) {
+        let (personnel_id, duree) = match self.rendez_vous.iter().find(|r| r.id == rdv_id) {
+            Some(rdv) => (rdv.personnel_id, rdv.duree),
+            None => {
+                println!("{}", "Rendez-vous non trouvé!".red());
+                return;
+            }
+        };
+
+        let date = lire_chaine("Nouvelle date (JJ/MM/AAAA): ");
+        let heure = lire_chaine("Nouvelle heure (HH:MM): ");
+        let motif = lire_chaine("Motif du déplacement: ");
+
+        match self.deplacer(rdv_id, date, heure, &motif) {
+            Ok(nouveau_id) => {
+                self.save_data();
+                println!(
+                    "{}",
+                    format!("\nRendez-vous déplacé (nouveau N°{})!", nouveau_id).green()
+                );
+            }
+            Err(erreur) => {
+                println!("{}", erreur.red());
+                println!("\nProchains créneaux libres pour ce médecin:");
+                self.proposer_creneaux(personnel_id, duree);
+            }
+        }
+    }
+
+    pub fn liste_absences(&self) {
+        println!("{}", "\n=== ABSENCES PAR PATIENT ===".green());
+        for patient in &self.patients {
+            let absences = self.absences_patient(patient.id);
+            if absences == 0 {
+                continue;
+            }
+            let total = self
+                .rendez_vous
+                .iter()
+                .filter(|r| r.patient_id == patient.id)
+                .count();
+            println!(
+                "{} {}: {} absence(s) sur {} rendez-vous",
+                patient.nom, patient.prenom, absences, total
+            );
+        }
+    }
+}
@@ -0,0 +1,454 @@
+use chrono::{Duration, NaiveDateTime};
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::gardes::est_jour_special;
+use crate::rendez_vous::{HEURE_FERMETURE, HEURE_OUVERTURE};
+use crate::{
+    lire_chaine, lire_nombre, maintenant, parse_date, parse_date_heure, Application,
+    StatutEquipement, FORMAT_DATE_HEURE,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum ObjetReservation {
+    RendezVous(u32),
+    Intervention(u32),
+    Procedure(String),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReservationEquipement {
+    id: u32,
+    service_id: u32,
+    equipement_id: u32,
+    debut: String,
+    duree: u32,
+    objet: ObjetReservation,
+    personnel_id: Option<u32>,
+    creee_le: String,
+    annulee: bool,
+}
+
+impl ReservationEquipement {
+    pub fn active(&self) -> bool {
+        !self.annulee
+    }
+
+    pub fn creneau(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let debut = parse_date_heure(&self.debut)?;
+        Some((debut, debut + Duration::minutes(self.duree as i64)))
+    }
+
+    fn chevauche(&self, debut: NaiveDateTime, fin: NaiveDateTime) -> bool {
+        self.active() && self.creneau().is_some_and(|(d, f)| d < fin && debut < f)
+    }
+
+    pub fn rendez_vous_id(&self) -> Option<u32> {
+        match self.objet {
+            ObjetReservation::RendezVous(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn libelle(&self) -> String {
+        match &self.objet {
+            ObjetReservation::RendezVous(id) => format!("RDV N°{}", id),
+            ObjetReservation::Intervention(id) => format!("Intervention N°{}", id),
+            ObjetReservation::Procedure(nom) => nom.clone(),
+        }
+    }
+}
+
+impl Application {
+    // Réserve un équipement après contrôle de son état, de son échéancier
+    // de maintenance, des réservations existantes et de l'habilitation de l'opérateur
+    pub fn reserver_equipement(
+        &mut self,
+        service_id: u32,
+        equipement_id: u32,
+        debut: NaiveDateTime,
+        duree: u32,
+        objet: ObjetReservation,
+        personnel_id: Option<u32>,
+    ) -> Result<u32, String> {
+        let equipement = self
+            .services
+            .iter()
+            .find(|s| s.id == service_id)
+            .and_then(|s| s.equipements.iter().find(|e| e.id == equipement_id))
+            .ok_or("Équipement non trouvé")?;
+        if duree == 0 {
+            return Err("La durée doit être positive".to_string());
+        }
+        let fin = debut + Duration::minutes(duree as i64);
+
+        if equipement.statut != StatutEquipement::Fonctionnel {
+            return Err(format!(
+                "{} est {}",
+                equipement.nom,
+                equipement.statut.libelle().to_lowercase()
+            ));
+        }
+        // Même règle que l'alerte de maintenance: l'échéance dépassée bloque l'appareil
+        if equipement.maintenance_en_retard(chrono::Local::now().date_naive()) {
+            return Err(format!(
+                "Maintenance de {} en retard depuis le {}",
+                equipement.nom, equipement.prochaine_maintenance
+            ));
+        }
+        if let Some(maintenance) = equipement.prochaine_maintenance() {
+            if debut.date() <= maintenance && maintenance <= fin.date() {
+                return Err(format!(
+                    "Maintenance de {} prévue le {}",
+                    equipement.nom, equipement.prochaine_maintenance
+                ));
+            }
+        }
+        if let ObjetReservation::RendezVous(rdv_id) = objet {
+            if !self.rendez_vous.iter().any(|r| r.id == rdv_id && r.actif()) {
+                return Err(format!("Rendez-vous {} inconnu ou inactif", rdv_id));
+            }
+        }
+        if let Some(personnel_id) = personnel_id {
+            self.verifier_habilitation(personnel_id, service_id, equipement_id, debut.date())?;
+        }
+        if let Some(conflit) = self
+            .reservations_equipement
+            .iter()
+            .find(|r| r.equipement_id == equipement_id && r.chevauche(debut, fin))
+        {
+            return Err(format!(
+                "Déjà réservé le {} pour {} (réservation N°{})",
+                conflit.debut,
+                conflit.libelle(),
+                conflit.id
+            ));
+        }
+
+        let id = (self.reservations_equipement.len() + 1) as u32;
+        self.reservations_equipement.push(ReservationEquipement {
+            id,
+            service_id,
+            equipement_id,
+            debut: debut.format(FORMAT_DATE_HEURE).to_string(),
+            duree,
+            objet,
+            personnel_id,
+            creee_le: maintenant(),
+            annulee: false,
+        });
+        Ok(id)
+    }
+
+    // Réservations actives d'un équipement qui ne sont pas encore terminées
+    pub fn reservations_a_venir(
+        &self,
+        equipement_id: u32,
+        depuis: NaiveDateTime,
+    ) -> Vec<&ReservationEquipement> {
+        self.reservations_equipement
+            .iter()
+            .filter(|r| r.active() && r.equipement_id == equipement_id)
+            .filter(|r| r.creneau().is_some_and(|(_, fin)| fin > depuis))
+            .collect()
+    }
+
+    pub fn liberer_reservations(&mut self, objet: &ObjetReservation) -> usize {
+        let mut liberees = 0;
+        for reservation in self
+            .reservations_equipement
+            .iter_mut()
+            .filter(|r| r.active() && r.objet == *objet)
+        {
+            reservation.annulee = true;
+            liberees += 1;
+        }
+        liberees
+    }
+
+    // Libère les équipements réservés pour un rendez-vous annulé
+    pub fn liberer_reservations_rdv(&mut self, rdv_id: u32) -> usize {
+        self.liberer_reservations(&ObjetReservation::RendezVous(rdv_id))
+    }
+
+    // Déplace toutes les réservations actives d'un objet au nouveau début, en gardant
+    // leur durée sauf si `duree` est donnée; en cas d'échec rien n'est modifié
+    pub fn deplacer_reservations(
+        &mut self,
+        objet: &ObjetReservation,
+        debut: NaiveDateTime,
+        duree: Option<u32>,
+    ) -> Result<usize, String> {
+        let sauvegarde = self.reservations_equipement.clone();
+        let a_deplacer: Vec<ReservationEquipement> = self
+            .reservations_equipement
+            .iter()
+            .filter(|r| r.active() && r.objet == *objet)
+            .cloned()
+            .collect();
+        self.liberer_reservations(objet);
+        for reservation in &a_deplacer {
+            if let Err(erreur) = self.reserver_equipement(
+                reservation.service_id,
+                reservation.equipement_id,
+                debut,
+                duree.unwrap_or(reservation.duree),
+                objet.clone(),
+                reservation.personnel_id,
+            ) {
+                self.reservations_equipement = sauvegarde;
+                return Err(format!("Réservation N°{}: {}", reservation.id, erreur));
+            }
+        }
+        Ok(a_deplacer.len())
+    }
+
+    // Reporte les réservations d'un rendez-vous déplacé sur le nouveau rendez-vous
+    pub fn reporter_reservations(&mut self, ancien_id: u32, nouveau_id: u32) {
+        let a_reporter: Vec<ReservationEquipement> = self
+            .reservations_equipement
+            .iter()
+            .filter(|r| r.active() && r.objet == ObjetReservation::RendezVous(ancien_id))
+            .cloned()
+            .collect();
+        self.liberer_reservations_rdv(ancien_id);
+        let debut = match self
+            .rendez_vous
+            .iter()
+            .find(|r| r.id == nouveau_id)
+            .and_then(|r| r.debut())
+        {
+            Some(debut) => debut,
+            None => return,
+        };
+        for reservation in a_reporter {
+            if let Err(erreur) = self.reserver_equipement(
+                reservation.service_id,
+                reservation.equipement_id,
+                debut,
+                reservation.duree,
+                ObjetReservation::RendezVous(nouveau_id),
+                reservation.personnel_id,
+            ) {
+                println!(
+                    "{}",
+                    format!(
+                        "⚠️ Réservation N°{} non reportée: {}",
+                        reservation.id, erreur
+                    )
+                    .yellow()
+                );
+            }
+        }
+    }
+
+    fn reserver_equipement_interactif(&mut self) {
+        println!("{}", "\n=== RÉSERVATION D'UN ÉQUIPEMENT ===".green());
+        let (service_id, equipement_id) = match self.choisir_equipement() {
+            Some(choix) => choix,
+            None => return,
+        };
+
+        println!("1. Pour un rendez-vous");
+        println!("2. Pour une procédure");
+        let (objet, debut, duree, personnel_id) = match lire_nombre("Choix: ") {
+            1 => {
+                self.liste_rendez_vous();
+                let rdv_id = lire_nombre("ID du rendez-vous: ");
+                let rdv = match self.rendez_vous.iter().find(|r| r.id == rdv_id) {
+                    Some(rdv) => rdv,
+                    None => {
+                        println!("{}", "Rendez-vous non trouvé!".red());
+                        return;
+                    }
+                };
+                let debut = match rdv.debut() {
+                    Some(debut) => debut,
+                    None => return,
+                };
+                (
+                    ObjetReservation::RendezVous(rdv_id),
+                    debut,
+                    rdv.duree,
+                    Some(rdv.personnel_id),
+                )
+            }
+            2 => {
+                let procedure = lire_chaine("Procédure: ");
+                let debut = match parse_date_heure(&lire_chaine("Début (JJ/MM/AAAA HH:MM): ")) {
+                    Some(debut) => debut,
+                    None => {
+                        println!("{}", "Date ou heure invalide!".red());
+                        return;
+                    }
+                };
+                let duree = lire_nombre("Durée (minutes): ");
+                let saisie = lire_chaine("ID de l'opérateur (Entrée si non précisé): ");
+                (
+                    ObjetReservation::Procedure(procedure),
+                    debut,
+                    duree,
+                    saisie.parse().ok(),
+                )
+            }
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+
+        match self.reserver_equipement(service_id, equipement_id, debut, duree, objet, personnel_id)
+        {
+            Ok(id) => {
+                self.save_data();
+                println!("{}", format!("\nRéservation N°{} enregistrée!", id).green());
+            }
+            Err(erreur) => println!("{}", erreur.red()),
+        }
+    }
+
+    fn liste_reservations_equipement(&self) {
+        let (_, equipement_id) = match self.choisir_equipement() {
+            Some(choix) => choix,
+            None => return,
+        };
+        println!("{}", "\n=== RÉSERVATIONS ===".green());
+        let mut reservations: Vec<&ReservationEquipement> = self
+            .reservations_equipement
+            .iter()
+            .filter(|r| r.equipement_id == equipement_id)
+            .collect();
+        reservations.sort_by_key(|r| r.creneau().map(|(debut, _)| debut));
+        for reservation in reservations {
+            let ligne = format!(
+                "N°{} - {} ({} min): {}",
+                reservation.id,
+                reservation.debut,
+                reservation.duree,
+                reservation.libelle()
+            );
+            if reservation.active() {
+                println!("{}", ligne);
+            } else {
+                println!("{}", format!("{} - annulée", ligne).red());
+            }
+        }
+    }
+
+    fn annuler_reservation_equipement(&mut self) {
+        println!("{}", "\n=== ANNULATION D'UNE RÉSERVATION ===".green());
+        let id = lire_nombre("ID de la réservation: ");
+        match self
+            .reservations_equipement
+            .iter_mut()
+            .find(|r| r.id == id && r.active())
+        {
+            Some(reservation) => {
+                reservation.annulee = true;
+                self.save_data();
+                println!("{}", "\nRéservation annulée!".green());
+            }
+            None => println!("{}", "Réservation active non trouvée!".red()),
+        }
+    }
+
+    // Taux d'occupation des équipements d'un service sur la plage d'ouverture
+    fn utilisation_equipements(&self) {
+        println!("{}", "\n=== UTILISATION DES ÉQUIPEMENTS ===".green());
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        let service = match self.services.iter().find(|s| s.id == service_id) {
+            Some(service) => service,
+            None => {
+                println!("{}", "Service non trouvé!".red());
+                return;
+            }
+        };
+        let (du, au) = match (
+            parse_date(&lire_chaine("Du (JJ/MM/AAAA): ")),
+            parse_date(&lire_chaine("Au (JJ/MM/AAAA): ")),
+        ) {
+            (Some(du), Some(au)) if du <= au => (du, au),
+            _ => {
+                println!("{}", "Période invalide!".red());
+                return;
+            }
+        };
+        // Plages d'ouverture des jours ouvrés de la période, week-ends et fériés exclus
+        let plages: Vec<(NaiveDateTime, NaiveDateTime)> = du
+            .iter_days()
+            .take_while(|d| *d <= au)
+            .filter(|d| !est_jour_special(*d))
+            .filter_map(|d| {
+                Some((
+                    d.and_hms_opt(HEURE_OUVERTURE, 0, 0)?,
+                    d.and_hms_opt(HEURE_FERMETURE, 0, 0)?,
+                ))
+            })
+            .collect();
+        let minutes_ouvertes: i64 = plages.iter().map(|(o, f)| (*f - *o).num_minutes()).sum();
+
+        println!(
+            "{}",
+            format!(
+                "\n--- {} ({} jour(s) ouvré(s), {}h-{}h) ---",
+                service.nom,
+                plages.len(),
+                HEURE_OUVERTURE,
+                HEURE_FERMETURE
+            )
+            .bold()
+        );
+        for equipement in &service.equipements {
+            let reservations: Vec<(NaiveDateTime, NaiveDateTime)> = self
+                .reservations_equipement
+                .iter()
+                .filter(|r| r.active() && r.equipement_id == equipement.id)
+                .filter_map(|r| r.creneau())
+                .filter(|(d, f)| plages.iter().any(|(o, fe)| d < fe && o < f))
+                .collect();
+            // Seules les minutes réservées pendant les heures d'ouverture comptent
+            let minutes: i64 = reservations
+                .iter()
+                .flat_map(|(d, f)| {
+                    plages
+                        .iter()
+                        .map(move |(o, fe)| ((*f).min(*fe) - (*d).max(*o)).num_minutes().max(0))
+                })
+                .sum();
+            println!(
+                "{} N°{}: {} réservation(s), {}h{:02} réservées, {:.1}% d'occupation",
+                equipement.nom,
+                equipement.id,
+                reservations.len(),
+                minutes / 60,
+                minutes % 60,
+                if minutes_ouvertes > 0 {
+                    minutes as f64 * 100.0 / minutes_ouvertes as f64
+                } else {
+                    0.0
+                }
+            );
+        }
+    }
+
+    pub fn menu_reservations_equipement(&mut self) {
+        loop {
+            println!("\n{}", "=== RÉSERVATIONS D'ÉQUIPEMENTS ===".blue().bold());
+            println!("1. Réserver un équipement");
+            println!("2. Réservations d'un équipement");
+            println!("3. Annuler une réservation");
+            println!("4. Utilisation des équipements d'un service");
+            println!("5. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.reserver_equipement_interactif(),
+                2 => self.liste_reservations_equipement(),
+                3 => self.annuler_reservation_equipement(),
+                4 => self.utilisation_equipements(),
+                5 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use colored::*;
+
+use crate::{lire_chaine, lire_nombre, maintenant, Application, Service};
+
+impl Service {
+    pub fn actif(&self) -> bool {
+        self.desactive_le.is_none()
+    }
+}
+
+impl Application {
+    // Le chef de service doit exister et ne pas avoir quitté l'établissement
+    pub fn verifier_chef_service(&self, personnel_id: u32) -> Result<(), String> {
+        match self.personnel.iter().find(|p| p.id == personnel_id) {
+            Some(membre) if membre.actif() => Ok(()),
+            Some(membre) => Err(format!(
+                "{} {} a quitté l'établissement",
+                membre.nom, membre.prenom
+            )),
+            None => Err(format!("Membre du personnel {} inconnu", personnel_id)),
+        }
+    }
+
+    pub fn service_actif(&self, service_id: u32) -> Result<usize, String> {
+        let index = self
+            .services
+            .iter()
+            .position(|s| s.id == service_id)
+            .ok_or("Service non trouvé!")?;
+        if !self.services[index].actif() {
+            return Err(format!(
+                "Le service {} est désactivé",
+                self.services[index].nom
+            ));
+        }
+        Ok(index)
+    }
+
+    pub fn affecter_personnel(&mut self, service_id: u32, personnel_id: u32) -> Result<(), String> {
+        let index = self.service_actif(service_id)?;
+        match self.personnel.iter().find(|p| p.id == personnel_id) {
+            Some(membre) if !membre.actif() => {
+                return Err(format!(
+                    "{} {} a quitté l'établissement",
+                    membre.nom, membre.prenom
+                ))
+            }
+            Some(_) => {}
+            None => return Err(format!("Membre du personnel {} inconnu", personnel_id)),
+        }
+        let service = &mut self.services[index];
+        if service.personnel_affecte.contains(&personnel_id) {
+            return Err("Ce membre est déjà affecté au service".to_string());
+        }
+        service.personnel_affecte.push(personnel_id);
+        Ok(())
+    }
+
+    pub fn retirer_personnel(&mut self, service_id: u32, personnel_id: u32) -> Result<(), String> {
+        let index = self.service_actif(service_id)?;
+        let service = &mut self.services[index];
+        if service.chef_service == personnel_id {
+            return Err("Le chef de service doit d'abord être remplacé".to_string());
+        }
+        let position = service
+            .personnel_affecte
+            .iter()
+            .position(|id| *id == personnel_id)
+            .ok_or("Ce membre n'est pas affecté au service")?;
+        service.personnel_affecte.remove(position);
+        Ok(())
+    }
+
+    pub fn changer_chef_service(
+        &mut self,
+        service_id: u32,
+        personnel_id: u32,
+    ) -> Result<(), String> {
+        let index = self.service_actif(service_id)?;
+        self.verifier_chef_service(personnel_id)?;
+        let service = &mut self.services[index];
+        if service.chef_service == personnel_id {
+            return Err("Ce membre est déjà chef du service".to_string());
+        }
+        service.chef_service = personnel_id;
+        if !service.personnel_affecte.contains(&personnel_id) {
+            service.personnel_affecte.push(personnel_id);
+        }
+        Ok(())
+    }
+
+    // La capacité ne peut pas descendre sous le numéro d'un lit occupé
+    pub fn modifier_capacite(&mut self, service_id: u32, capacite: u32) -> Result<(), String> {
+        let index = self.service_actif(service_id)?;
+        if capacite == 0 {
+            return Err("La capacité doit être positive".to_string());
+        }
+        if let Some(lit) = self
+            .lits_occupes(service_id)
+            .into_iter()
+            .filter(|lit| *lit > capacite)
+            .max()
+        {
+            return Err(format!("Le lit {} est encore occupé", lit));
+        }
+        self.services[index].capacite = capacite;
+        Ok(())
+    }
+
+    pub fn desactiver_service(&mut self, service_id: u32) -> Result<(), String> {
+        let index = self.service_actif(service_id)?;
+        let occupes = self.lits_occupes(service_id).len();
+        if occupes > 0 {
+            return Err(format!(
+                "{} patient(s) encore hospitalisé(s) dans le service",
+                occupes
+            ));
+        }
+        self.services[index].desactive_le = Some(maintenant());
+        Ok(())
+    }
+
+    fn choisir_service_et_membre(&self) -> Option<(u32, u32)> {
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        if !self.services.iter().any(|s| s.id == service_id) {
+            println!("{}", "Service non trouvé!".red());
+            return None;
+        }
+        self.liste_personnel();
+        let personnel_id = lire_nombre("ID du membre du personnel: ");
+        Some((service_id, personnel_id))
+    }
+
+    fn afficher_resultat(&mut self, resultat: Result<(), String>, message: &str) {
+        match resultat {
+            Ok(()) => {
+                self.save_data();
+                println!("{}", message.green());
+            }
+            Err(erreur) => println!("{}", erreur.red()),
+        }
+    }
+
+    fn affecter_personnel_interactif(&mut self) {
+        println!("{}", "\n=== AFFECTATION AU SERVICE ===".green());
+        if let Some((service_id, personnel_id)) = self.choisir_service_et_membre() {
+            let resultat = self.affecter_personnel(service_id, personnel_id);
+            self.afficher_resultat(resultat, "\nMembre affecté au service!");
+        }
+    }
+
+    fn retirer_personnel_interactif(&mut self) {
+        println!("{}", "\n=== RETRAIT DU SERVICE ===".green());
+        if let Some((service_id, personnel_id)) = self.choisir_service_et_membre() {
+            let resultat = self.retirer_personnel(service_id, personnel_id);
+            self.afficher_resultat(resultat, "\nMembre retiré du service!");
+        }
+    }
+
+    fn changer_chef_interactif(&mut self) {
+        println!("{}", "\n=== CHANGEMENT DE CHEF DE SERVICE ===".green());
+        if let Some((service_id, personnel_id)) = self.choisir_service_et_membre() {
+            let resultat = self.changer_chef_service(service_id, personnel_id);
+            self.afficher_resultat(resultat, "\nChef de service modifié!");
+        }
+    }
+
+    fn modifier_capacite_interactif(&mut self) {
+        println!("{}", "\n=== CAPACITÉ D'UN SERVICE ===".green());
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        let capacite = lire_nombre("Nouvelle capacité: ");
+        let resultat = self.modifier_capacite(service_id, capacite);
+        self.afficher_resultat(resultat, "\nCapacité modifiée!");
+    }
+
+    fn desactiver_service_interactif(&mut self) {
+        println!("{}", "\n=== DÉSACTIVATION D'UN SERVICE ===".green());
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        let confirmation = lire_chaine("Confirmer la désactivation (o/n): ");
+        if !confirmation.eq_ignore_ascii_case("o") {
+            return;
+        }
+        let resultat = self.desactiver_service(service_id);
+        self.afficher_resultat(resultat, "\nService désactivé!");
+    }
+
+    fn personnel_du_service(&self) {
+        self.liste_services();
+        let service_id = lire_nombre("ID du service: ");
+        let service = match self.services.iter().find(|s| s.id == service_id) {
+            Some(service) => service,
+            None => {
+                println!("{}", "Service non trouvé!".red());
+                return;
+            }
+        };
+        println!(
+            "{}",
+            format!("\n=== PERSONNEL DU SERVICE {} ===", service.nom).green()
+        );
+        for id in &service.personnel_affecte {
+            let chef = if *id == service.chef_service {
+                " - chef de service"
+            } else {
+                ""
+            };
+            println!("{}{}", self.nom_personnel(*id), chef);
+        }
+    }
+
+    pub fn menu_gestion_service(&mut self) {
+        loop {
+            println!("\n{}", "=== MODIFIER UN SERVICE ===".blue().bold());
+            println!("1. Affecter un membre du personnel");
+            println!("2. Retirer un membre du personnel");
+            println!("3. Personnel d'un service");
+            println!("4. Changer le chef de service");
+            println!("5. Modifier la capacité");
+            println!("6. Désactiver un service");
+            println!("7. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.affecter_personnel_interactif(),
+                2 => self.retirer_personnel_interactif(),
+                3 => self.personnel_du_service(),
+                4 => self.changer_chef_interactif(),
+                5 => self.modifier_capacite_interactif(),
+                6 => self.desactiver_service_interactif(),
+                7 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use chrono::NaiveDate;
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{lire_chaine, lire_nombre, maintenant, parse_date, Application, Personnel};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum StatutPersonnel {
+    // Les anciennes données enregistraient le statut en texte libre
+    #[default]
+    #[serde(alias = "En service")]
+    EnService,
+    EnConge,
+    EnArret,
+    Suspendu,
+    Parti,
+}
+
+impl StatutPersonnel {
+    pub fn libelle(&self) -> &'static str {
+        match self {
+            StatutPersonnel::EnService => "En service",
+            StatutPersonnel::EnConge => "En congé",
+            StatutPersonnel::EnArret => "En arrêt",
+            StatutPersonnel::Suspendu => "Suspendu",
+            StatutPersonnel::Parti => "Parti",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ChangementStatutPersonnel {
+    date_effet: String,
+    ancien: StatutPersonnel,
+    nouveau: StatutPersonnel,
+    motif: String,
+    saisi_le: String,
+}
+
+fn indisponible(statut: StatutPersonnel) -> bool {
+    matches!(
+        statut,
+        StatutPersonnel::EnArret | StatutPersonnel::Suspendu | StatutPersonnel::Parti
+    )
+}
+
+impl Personnel {
+    // Un membre parti reste dans les données pour l'historique mais ne prend plus de rendez-vous
+    pub fn actif(&self) -> bool {
+        self.status != StatutPersonnel::Parti
+    }
+
+    // Un membre en arrêt ou suspendu ne compte pas dans l'effectif d'un service
+    pub fn disponible(&self) -> bool {
+        !indisponible(self.status)
+    }
+
+    // Statut à une date donnée d'après l'historique, changements à venir compris
+    pub fn statut_le(&self, date: NaiveDate) -> StatutPersonnel {
+        let mut statut = match self.historique_statut.first() {
+            Some(premier) => premier.ancien,
+            None => return self.status,
+        };
+        for changement in &self.historique_statut {
+            if parse_date(&changement.date_effet).is_some_and(|d| d <= date) {
+                statut = changement.nouveau;
+            }
+        }
+        statut
+    }
+
+    pub fn actif_le(&self, date: NaiveDate) -> bool {
+        self.statut_le(date) != StatutPersonnel::Parti
+    }
+
+    pub fn disponible_le(&self, date: NaiveDate) -> bool {
+        !indisponible(self.statut_le(date))
+    }
+
+    // Dernier statut saisi, qu'il soit déjà en vigueur ou programmé
+    fn statut_prevu(&self) -> StatutPersonnel {
+        self.historique_statut
+            .last()
+            .map(|c| c.nouveau)
+            .unwrap_or(self.status)
+    }
+}
+
+impl Application {
+    // Nom affiché dans les rendez-vous, notes et prescriptions, y compris pour le personnel parti
+    pub fn nom_personnel(&self, personnel_id: u32) -> String {
+        match self.personnel.iter().find(|p| p.id == personnel_id) {
+            Some(membre) if membre.actif() => format!("Dr. {} {}", membre.nom, membre.prenom),
+            Some(membre) => format!("Dr. {} {} (parti)", membre.nom, membre.prenom),
+            None => format!("Personnel N°{} inconnu", personnel_id),
+        }
+    }
+
+    // Applique les changements de statut dont la date d'effet est atteinte
+    pub fn appliquer_statuts_echus(&mut self) {
+        let aujourd_hui = chrono::Local::now().date_naive();
+        let echus: Vec<(usize, StatutPersonnel)> = self
+            .personnel
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i, p.statut_le(aujourd_hui)))
+            .filter(|(i, statut)| self.personnel[*i].status != *statut)
+            .collect();
+        if echus.is_empty() {
+            return;
+        }
+        for (index, statut) in echus {
+            let membre = &mut self.personnel[index];
+            membre.status = statut;
+            println!(
+                "{}",
+                format!(
+                    "Statut de {} {} passé à: {}",
+                    membre.nom,
+                    membre.prenom,
+                    statut.libelle()
+                )
+                .yellow()
+            );
+            if statut == StatutPersonnel::Parti {
+                let personnel_id = membre.id;
+                self.retirer_des_services(personnel_id);
+            }
+        }
+        self.save_data();
+    }
+
+    // Un membre parti quitte ses services; le chef reste en place jusqu'à son remplacement
+    fn retirer_des_services(&mut self, personnel_id: u32) {
+        for service in &mut self.services {
+            if service.chef_service == personnel_id {
+                println!(
+                    "{}",
+                    format!(
+                        "⚠️ Le service {} doit désigner un nouveau chef.",
+                        service.nom
+                    )
+                    .yellow()
+                );
+            } else {
+                service.personnel_affecte.retain(|id| *id != personnel_id);
+            }
+        }
+    }
+
+    fn changer_statut_personnel(&mut self) {
+        println!("{}", "\n=== CHANGEMENT DE STATUT ===".green());
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let ancien = self.personnel[index].statut_prevu();
+        if ancien == StatutPersonnel::Parti {
+            println!(
+                "{}",
+                "Ce membre a quitté ou va quitter l'établissement, son statut est définitif!".red()
+            );
+            return;
+        }
+
+        println!("Statut actuel: {}", self.personnel[index].status.libelle());
+        if ancien != self.personnel[index].status {
+            println!("Statut programmé: {}", ancien.libelle());
+        }
+        println!("1. En service");
+        println!("2. En congé");
+        println!("3. En arrêt");
+        println!("4. Suspendu");
+        println!("5. Parti");
+        let nouveau = match lire_nombre("Nouveau statut: ") {
+            1 => StatutPersonnel::EnService,
+            2 => StatutPersonnel::EnConge,
+            3 => StatutPersonnel::EnArret,
+            4 => StatutPersonnel::Suspendu,
+            5 => StatutPersonnel::Parti,
+            _ => {
+                println!("{}", "Choix invalide!".red());
+                return;
+            }
+        };
+        if nouveau == ancien {
+            println!("{}", "Le statut est inchangé!".red());
+            return;
+        }
+
+        let date_effet = lire_chaine("Date d'effet (JJ/MM/AAAA): ");
+        let effet = match parse_date(&date_effet) {
+            Some(date) => date,
+            None => {
+                println!("{}", "Date invalide!".red());
+                return;
+            }
+        };
+        if let Some(precedent) = self.personnel[index].historique_statut.last() {
+            if parse_date(&precedent.date_effet).is_some_and(|d| effet < d) {
+                println!(
+                    "{}",
+                    format!(
+                        "La date d'effet précède le dernier changement ({})!",
+                        precedent.date_effet
+                    )
+                    .red()
+                );
+                return;
+            }
+        }
+        let motif = lire_chaine("Motif: ");
+
+        // Un changement daté dans le futur ne s'applique qu'à sa date d'effet
+        let en_vigueur = effet <= chrono::Local::now().date_naive();
+        let membre = &mut self.personnel[index];
+        if en_vigueur {
+            membre.status = nouveau;
+        }
+        membre.historique_statut.push(ChangementStatutPersonnel {
+            date_effet,
+            ancien,
+            nouveau,
+            motif,
+            saisi_le: maintenant(),
+        });
+        let personnel_id = membre.id;
+
+        if nouveau == StatutPersonnel::Parti {
+            let a_venir: Vec<u32> = self
+                .rendez_vous
+                .iter()
+                .filter(|r| r.personnel_id == personnel_id && r.actif())
+                .filter(|r| parse_date(&r.date).is_some_and(|d| d >= effet))
+                .map(|r| r.id)
+                .collect();
+            for rdv in self
+                .rendez_vous
+                .iter_mut()
+                .filter(|r| a_venir.contains(&r.id))
+            {
+                rdv.a_replanifier = true;
+            }
+            if !a_venir.is_empty() {
+                println!(
+                    "{}",
+                    format!("{} rendez-vous à réattribuer.", a_venir.len()).yellow()
+                );
+            }
+            if en_vigueur {
+                self.retirer_des_services(personnel_id);
+            }
+        }
+        self.save_data();
+        if en_vigueur {
+            println!("{}", "\nStatut mis à jour!".green());
+        } else {
+            println!(
+                "{}",
+                format!("\nChangement programmé au {}!", effet.format("%d/%m/%Y")).green()
+            );
+        }
+    }
+
+    fn historique_statut_personnel(&self) {
+        let index = match self.choisir_membre_personnel() {
+            Some(index) => index,
+            None => return,
+        };
+        let membre = &self.personnel[index];
+        println!(
+            "{}",
+            format!(
+                "\n=== HISTORIQUE DU STATUT DE {} {} ===",
+                membre.nom, membre.prenom
+            )
+            .green()
+        );
+        println!("Statut actuel: {}", membre.status.libelle());
+        let aujourd_hui = chrono::Local::now().date_naive();
+        for changement in &membre.historique_statut {
+            println!("{}", "-".repeat(40));
+            println!(
+                "Le {}: {} -> {}",
+                changement.date_effet,
+                changement.ancien.libelle(),
+                changement.nouveau.libelle()
+            );
+            if parse_date(&changement.date_effet).is_some_and(|d| d > aujourd_hui) {
+                println!("{}", "(programmé)".yellow());
+            }
+            if !changement.motif.is_empty() {
+                println!("Motif: {}", changement.motif);
+            }
+            println!("Saisi le: {}", changement.saisi_le);
+        }
+    }
+
+    pub fn menu_statut_personnel(&mut self) {
+        loop {
+            println!("\n{}", "=== STATUT DU PERSONNEL ===".blue().bold());
+            println!("1. Changer le statut d'un membre");
+            println!("2. Historique du statut d'un membre");
+            println!("3. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.changer_statut_personnel(),
+                2 => self.historique_statut_personnel(),
+                3 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{lire_chaine, lire_nombre, maintenant, parse_date_heure, Application, NiveauUrgence};
+
+impl NiveauUrgence {
+    // Délai maximal d'attente avant prise en charge, en minutes
+    pub fn attente_max(&self) -> i64 {
+        match self {
+            NiveauUrgence::Critique => 5,
+            NiveauUrgence::Eleve => 20,
+            NiveauUrgence::Moyen => 60,
+            NiveauUrgence::Faible => 120,
+        }
+    }
+
+    fn libelle(&self) -> ColoredString {
+        match self {
+            NiveauUrgence::Critique => "Critique".red().bold(),
+            NiveauUrgence::Eleve => "Élevé".red(),
+            NiveauUrgence::Moyen => "Moyen".yellow(),
+            NiveauUrgence::Faible => "Faible".green(),
+        }
+    }
+}
+
+fn choisir_niveau() -> NiveauUrgence {
+    println!("Niveau de tri:");
+    println!("1. Faible");
+    println!("2. Moyen");
+    println!("3. Élevé");
+    println!("4. Critique");
+    match lire_nombre("Choix: ") {
+        4 => NiveauUrgence::Critique,
+        3 => NiveauUrgence::Eleve,
+        2 => NiveauUrgence::Moyen,
+        _ => NiveauUrgence::Faible,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum StatutPassage {
+    EnAttente,
+    Appele,
+    PrisEnCharge,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PassageUrgence {
+    id: u32,
+    patient_id: u32,
+    motif: String,
+    niveau: NiveauUrgence,
+    arrivee: String,
+    appel: Option<String>,
+    prise_en_charge: Option<String>,
+    medecin_id: Option<u32>,
+    statut: StatutPassage,
+}
+
+impl PassageUrgence {
+    // Attente en minutes jusqu'à l'appel, ou jusqu'à maintenant si le patient attend encore
+    fn attente(&self) -> i64 {
+        let arrivee = match parse_date_heure(&self.arrivee) {
+            Some(d) => d,
+            None => return 0,
+        };
+        let fin = self
+            .appel
+            .as_deref()
+            .and_then(parse_date_heure)
+            .unwrap_or_else(|| chrono::Local::now().naive_local());
+        (fin - arrivee).num_minutes().max(0)
+    }
+
+    fn en_retard(&self) -> bool {
+        self.statut == StatutPassage::EnAttente && self.attente() > self.niveau.attente_max()
+    }
+}
+
+impl Application {
+    // File d'attente triée par niveau (le plus grave d'abord) puis par heure d'arrivée
+    fn file_urgences(&self) -> Vec<&PassageUrgence> {
+        let mut file: Vec<&PassageUrgence> = self
+            .urgences
+            .iter()
+            .filter(|p| p.statut == StatutPassage::EnAttente)
+            .collect();
+        file.sort_by(|a, b| {
+            b.niveau.cmp(&a.niveau).then_with(|| {
+                parse_date_heure(&a.arrivee).cmp(&parse_date_heure(&b.arrivee))
+            })
+        });
+        file
+    }
+
+    pub fn enregistrer_arrivee_urgence(&mut self) {
+        println!("{}", "\n=== ACCUEIL DES URGENCES ===".green());
+        self.liste_patients();
+        let patient_id = lire_nombre("ID du patient: ");
+
+        if !self.patients.iter().any(|p| p.id == patient_id) {
+            println!("{}", "Patient non trouvé!".red());
+            return;
+        }
+        if self.urgences.iter().any(|p| {
+            p.patient_id == patient_id && p.statut != StatutPassage::PrisEnCharge
+        }) {
+            println!("{}", "Ce patient est déjà dans la file des urgences!".red());
+            return;
+        }
+
+        let motif = lire_chaine("Motif de venue: ");
+        let niveau = choisir_niveau();
+
+        let passage = PassageUrgence {
+            id: (self.urgences.len() + 1) as u32,
+            patient_id,
+            motif,
+            niveau,
+            arrivee: maintenant(),
+            appel: None,
+            prise_en_charge: None,
+            medecin_id: None,
+            statut: StatutPassage::EnAttente,
+        };
+
+        if let Some(patient) = self.patients.iter_mut().find(|p| p.id == patient_id) {
+            patient.niveau_urgence = Some(niveau);
+        }
+        self.urgences.push(passage);
+        self.save_data();
+        println!("{}", "\nPatient enregistré aux urgences!".green());
+    }
+
+    pub fn retrier_patient_urgence(&mut self) {
+        self.afficher_file_urgences();
+        let passage_id = lire_nombre("ID du passage à re-trier: ");
+
+        if let Some(passage) = self
+            .urgences
+            .iter_mut()
+            .find(|p| p.id == passage_id && p.statut == StatutPassage::EnAttente)
+        {
+            passage.niveau = choisir_niveau();
+            let (patient_id, niveau) = (passage.patient_id, passage.niveau);
+            if let Some(patient) = self.patients.iter_mut().find(|p| p.id == patient_id) {
+                patient.niveau_urgence = Some(niveau);
+            }
+            self.save_data();
+            println!("{}", "\nNiveau de tri mis à jour!".green());
+        } else {
+            println!("{}", "Passage non trouvé dans la file d'attente!".red());
+        }
+    }
+
+    pub fn afficher_file_urgences(&self) {
+        println!("{}", "\n=== FILE D'ATTENTE DES URGENCES ===".green());
+        let file = self.file_urgences();
+        if file.is_empty() {
+            println!("Aucun patient en attente.");
+        }
+        for (rang, passage) in file.iter().enumerate() {
+            println!("{}", "-".repeat(40));
+            println!("#{} - Passage N°{}", rang + 1, passage.id);
+            if let Some(patient) = self.patients.iter().find(|p| p.id == passage.patient_id) {
+                println!("Patient: {} {}", patient.nom, patient.prenom);
+            }
+            println!("Motif: {}", passage.motif);
+            println!("Niveau: {}", passage.niveau.libelle());
+            println!("Arrivée: {}", passage.arrivee);
+            println!(
+                "Attente: {} min (max {} min)",
+                passage.attente(),
+                passage.niveau.attente_max()
+            );
+            if passage.en_retard() {
+                println!("{}", "⚠️ Délai d'attente dépassé!".red());
+            }
+        }
+
+        let appeles: Vec<&PassageUrgence> = self
+            .urgences
+            .iter()
+            .filter(|p| p.statut == StatutPassage::Appele)
+            .collect();
+        if !appeles.is_empty() {
+            println!("\n--- Patients appelés, en attente d'un médecin ---");
+            for passage in appeles {
+                if let Some(patient) = self.patients.iter().find(|p| p.id == passage.patient_id) {
+                    println!(
+                        "Passage N°{}: {} {} ({:?})",
+                        passage.id, patient.nom, patient.prenom, passage.niveau
+                    );
+                }
+            }
+        }
+    }
+
+    pub fn appeler_patient_suivant(&mut self) {
+        let suivant = match self.file_urgences().first() {
+            Some(p) => p.id,
+            None => {
+                println!("{}", "Aucun patient en attente.".yellow());
+                return;
+            }
+        };
+
+        if let Some(passage) = self.urgences.iter_mut().find(|p| p.id == suivant) {
+            passage.statut = StatutPassage::Appele;
+            passage.appel = Some(maintenant());
+            let patient_id = passage.patient_id;
+            if let Some(patient) = self.patients.iter().find(|p| p.id == patient_id) {
+                println!(
+                    "{}",
+                    format!("\nPatient appelé: {} {}", patient.nom, patient.prenom).green()
+                );
+            }
+            self.save_data();
+        }
+    }
+
+    pub fn confier_patient_medecin(&mut self) {
+        println!("{}", "\n=== PRISE EN CHARGE MÉDICALE ===".green());
+        self.afficher_file_urgences();
+        let passage_id = lire_nombre("ID du passage: ");
+
+        if !self.urgences.iter().any(|p| {
+            p.id == passage_id && p.statut != StatutPassage::PrisEnCharge
+        }) {
+            println!("{}", "Passage non trouvé ou déjà pris en charge!".red());
+            return;
+        }
+
+        self.liste_personnel();
+        let medecin_id = lire_nombre("ID du médecin: ");
+        if !self.personnel.iter().any(|p| p.id == medecin_id) {
+            println!("{}", "Médecin non trouvé!".red());
+            return;
+        }
+
+        if let Some(passage) = self.urgences.iter_mut().find(|p| p.id == passage_id) {
+            let heure = maintenant();
+            if passage.appel.is_none() {
+                passage.appel = Some(heure.clone());
+            }
+            passage.prise_en_charge = Some(heure);
+            passage.medecin_id = Some(medecin_id);
+            passage.statut = StatutPassage::PrisEnCharge;
+            self.save_data();
+            println!("{}", "\nPatient confié au médecin!".green());
+        }
+    }
+
+    pub fn statistiques_urgences(&self) {
+        println!("\n--- Urgences ---");
+        let en_attente = self
+            .urgences
+            .iter()
+            .filter(|p| p.statut == StatutPassage::EnAttente)
+            .count();
+        let en_retard = self.urgences.iter().filter(|p| p.en_retard()).count();
+        println!("Patients en attente: {}", en_attente);
+        println!("Patients en dépassement de délai: {}", en_retard);
+
+        let attentes: Vec<i64> = self
+            .urgences
+            .iter()
+            .filter(|p| p.appel.is_some())
+            .map(|p| p.attente())
+            .collect();
+        if !attentes.is_empty() {
+            let moyenne = attentes.iter().sum::<i64>() as f64 / attentes.len() as f64;
+            println!("Attente moyenne avant appel: {:.0} min", moyenne);
+            println!("Attente maximale avant appel: {} min", attentes.iter().max().unwrap());
+        }
+
+        for niveau in [
+            NiveauUrgence::Critique,
+            NiveauUrgence::Eleve,
+            NiveauUrgence::Moyen,
+            NiveauUrgence::Faible,
+        ] {
+            let attentes: Vec<i64> = self
+                .urgences
+                .iter()
+                .filter(|p| p.niveau == niveau && p.appel.is_some())
+                .map(|p| p.attente())
+                .collect();
+            if !attentes.is_empty() {
+                let moyenne = attentes.iter().sum::<i64>() as f64 / attentes.len() as f64;
+                println!("  {:?}: {:.0} min en moyenne ({} passages)", niveau, moyenne, attentes.len());
+            }
+        }
+    }
+
+    pub fn menu_urgences(&mut self) {
+        loop {
+            println!("\n{}", "=== URGENCES ===".blue().bold());
+            println!("1. Enregistrer une arrivée");
+            println!("2. File d'attente");
+            println!("3. Appeler le patient suivant");
+            println!("4. Confier un patient à un médecin");
+            println!("5. Re-trier un patient");
+            println!("6. Retour");
+
+            match lire_nombre("\nChoix: ") {
+                1 => self.enregistrer_arrivee_urgence(),
+                2 => self.afficher_file_urgences(),
+                3 => self.appeler_patient_suivant(),
+                4 => self.confier_patient_medecin(),
+                5 => self.retrier_patient_urgence(),
+                6 => break,
+                _ => println!("{}", "Choix invalide!".red()),
+            }
+        }
+    }
+}